@@ -0,0 +1,10 @@
+// 埋め込みはクリックされてから読み込みます - Embeds are only loaded once clicked
+document.addEventListener("click", event => {
+    const facade = event.target.closest(".embed-facade")
+    // The title links to the original page instead.
+    if (facade === null || event.target.closest("a") !== null) {
+        return
+    }
+    const template = facade.querySelector("template")
+    facade.replaceWith(document.importNode(template.content, true))
+})
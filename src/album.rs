@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use toml::value::Date;
 
-use crate::{metadata::Metadata, templates::partials::navbar::Sections};
+use crate::publish::{ContentStatus, PublishAt};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct AlbumMeta {
@@ -35,6 +35,15 @@ pub struct AlbumMeta {
 
     #[serde(default)]
     pub sns_links: Vec<String>,
+
+    #[serde(default)]
+    pub status: ContentStatus,
+    #[serde(default)]
+    pub publish_at: Option<PublishAt>,
+    #[serde(default)]
+    pub slug: Option<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -53,6 +62,9 @@ pub struct TracklistTrack {
     pub duration_seconds: Option<i32>,
     #[serde(default)]
     pub link: Option<String>,
+    /// A file in `audio/` to read the duration and tags of.
+    #[serde(default)]
+    pub file: Option<String>,
     #[serde(default)]
     pub on_site: bool,
     #[serde(default)]
@@ -92,21 +104,6 @@ impl AlbumMeta {
     }
 }
 
-impl From<AlbumMeta> for Metadata {
-    fn from(value: AlbumMeta) -> Self {
-        let authors = value.contributors_str_naive();
-        Metadata {
-            canonical_link: format!("/works/albums/{}", &value.title),
-            page_title: value.title,
-            page_image: Some(value.front_cover),
-            section: Sections::Works,
-            description: Some(value.short),
-            author: Some(authors),
-            date: Some(value.release_date.to_string()),
-        }
-    }
-}
-
 #[derive(Clone, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub enum AlbumType {
     Solo,
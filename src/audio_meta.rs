@@ -0,0 +1,520 @@
+use crate::check::Diagnostics;
+use crate::read::SiteContent;
+use crate::util::audio_path;
+use anyhow::{Context as _, Error};
+use camino::Utf8Path;
+use std::collections::HashMap;
+use std::fs::File;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::{MediaSource, MediaSourceStream};
+use symphonia::core::meta::{MetadataOptions, MetadataRevision, StandardTagKey};
+use symphonia::core::probe::Hint;
+
+/// Durations in front matter this close to the file's are left alone, files are rarely cut to
+/// the exact second.
+const DURATION_TOLERANCE: f64 = 1.0;
+
+/// What an audio file says about itself. Anything it does not say is `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct AudioInfo {
+    duration_seconds: Option<f64>,
+    title: Option<String>,
+    artist: Option<String>,
+}
+
+/// Reads the local audio file of every work and album track that has one. The file's duration
+/// goes into `duration_seconds`, and front matter the file disagrees with is warned about.
+/// Tracks that are works on the site and have no duration take the work's.
+pub fn read_audio_metadata(
+    content: &mut SiteContent,
+    data_root: &Utf8Path,
+    diagnostics: &mut Diagnostics,
+) {
+    let name_map = content.name_map();
+    let artists = |author: &str| {
+        let mut artists = vec![author.to_string()];
+        artists.extend(name_map.get(author).cloned());
+        artists
+    };
+
+    for work in &mut content.works {
+        let meta = &mut work.data.meta;
+        if let Some(file) = meta
+            .file
+            .as_deref()
+            .filter(|file| !file.starts_with("miku:"))
+        {
+            meta.duration_seconds = compare(
+                data_root,
+                file,
+                &meta.title,
+                &artists(&meta.author),
+                meta.duration_seconds,
+                &work.file,
+                diagnostics,
+            );
+        }
+    }
+
+    let durations = content
+        .works
+        .iter()
+        .filter_map(|work| {
+            let meta = &work.data.meta;
+            Some((
+                (meta.title.as_str(), meta.author.as_str()),
+                meta.duration_seconds?,
+            ))
+        })
+        .collect::<HashMap<_, _>>();
+    for album in &mut content.albums {
+        for track in &mut album.data.meta.tracklist {
+            if let Some(file) = track
+                .file
+                .as_deref()
+                .filter(|file| !file.starts_with("miku:"))
+            {
+                track.duration_seconds = compare(
+                    data_root,
+                    file,
+                    &track.title,
+                    &artists(&track.author),
+                    track.duration_seconds,
+                    &album.file,
+                    diagnostics,
+                );
+            } else if track.on_site && track.duration_seconds.is_none() {
+                track.duration_seconds = durations
+                    .get(&(track.title.as_str(), track.author.as_str()))
+                    .copied();
+            }
+        }
+    }
+}
+
+/// Reads `file` and warns about where it disagrees with the `title`, author and `duration`
+/// written in `content_file`. Returns the duration to use, the file's if it has one.
+fn compare(
+    data_root: &Utf8Path,
+    file: &str,
+    title: &str,
+    artists: &[String],
+    duration: Option<i32>,
+    content_file: &Utf8Path,
+    diagnostics: &mut Diagnostics,
+) -> Option<i32> {
+    let path = data_root.join(audio_path(file));
+    if !path.is_file() {
+        diagnostics.error(
+            content_file,
+            format!("音声ファイル{path}は見つかりませんでした。 - Audio file {path} not found"),
+        );
+        return duration;
+    }
+    let info = match audio_info(&path) {
+        Ok(info) => info,
+        Err(why) => {
+            diagnostics.warning(
+                content_file,
+                format!(
+                    "音声ファイル{file}のメタデータを読み込めませんでした: {why:#} - Failed to read the metadata of {file}"
+                ),
+            );
+            return duration;
+        }
+    };
+
+    if let Some(tagged) = &info.title
+        && !same_name(tagged, title)
+    {
+        diagnostics.warning(
+            content_file,
+            format!(
+                "{file}の題名「{tagged}」は`title`の「{title}」と違います。 - The title tag of {file}, \"{tagged}\", differs from `title` \"{title}\""
+            ),
+        );
+    }
+    if let Some(tagged) = &info.artist
+        && !artists.iter().any(|artist| same_name(tagged, artist))
+    {
+        let author = &artists[0];
+        diagnostics.warning(
+            content_file,
+            format!(
+                "{file}のアーティスト「{tagged}」は`author`の「{author}」と違います。 - The artist tag of {file}, \"{tagged}\", differs from `author` \"{author}\""
+            ),
+        );
+    }
+
+    let Some(seconds) = info.duration_seconds else {
+        return duration;
+    };
+    let rounded = seconds.round() as i32;
+    if let Some(written) = duration
+        && (f64::from(written) - seconds).abs() > DURATION_TOLERANCE
+    {
+        diagnostics.warning(
+            content_file,
+            format!(
+                "メタデータフィルド`duration_seconds`は{written}秒ですが、{file}は{rounded}秒です。ファイルの長さを使います。 - `duration_seconds` is {written} but {file} is {rounded} seconds long, using the file's"
+            ),
+        );
+    }
+    Some(rounded)
+}
+
+/// Whether a tag and front matter name the same thing. Tags often add to the name, e.g.
+/// `Song (feat. Someone)`, so either may contain the other.
+fn same_name(tagged: &str, written: &str) -> bool {
+    let tagged = tagged.trim().to_lowercase();
+    let written = written.trim().to_lowercase();
+    tagged.contains(&written) || written.contains(&tagged)
+}
+
+/// Reads the duration, title and artist of the audio file at `path`, in any format symphonia
+/// can open. Opus is not decoded by symphonia, but its duration and tags come from the Ogg stream
+/// all the same.
+fn audio_info(path: &Utf8Path) -> Result<AudioInfo, Error> {
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension() {
+        hint.with_extension(extension);
+    }
+    read_info(Box::new(File::open(path)?), &hint)
+}
+
+fn read_info(source: Box<dyn MediaSource>, hint: &Hint) -> Result<AudioInfo, Error> {
+    let stream = MediaSourceStream::new(source, Default::default());
+    let mut probed = symphonia::default::get_probe()
+        .format(
+            hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .context("not an audio file")?;
+
+    let mut info = AudioInfo {
+        duration_seconds: probed.format.default_track().and_then(|track| {
+            let params = &track.codec_params;
+            let frames = params.n_frames?;
+            match params.time_base {
+                Some(time_base) => {
+                    let time = time_base.calc_time(frames);
+                    Some(time.seconds as f64 + time.frac)
+                }
+                None => Some(frames as f64 / f64::from(params.sample_rate?)),
+            }
+        }),
+        ..AudioInfo::default()
+    };
+    // Tags in the container come first, e.g. Vorbis comments, then those before it, e.g. ID3v2.
+    if let Some(revision) = probed.format.metadata().current() {
+        read_tags(revision, &mut info);
+    }
+    if let Some(revision) = probed
+        .metadata
+        .get()
+        .as_ref()
+        .and_then(|metadata| metadata.current())
+    {
+        read_tags(revision, &mut info);
+    }
+    Ok(info)
+}
+
+/// Fills in the title and artist of `info` it does not have yet from `revision`.
+fn read_tags(revision: &MetadataRevision, info: &mut AudioInfo) {
+    let values = |key: StandardTagKey| {
+        revision
+            .tags()
+            .iter()
+            .filter(|tag| tag.std_key == Some(key))
+            .map(|tag| tag.value.to_string().trim_end_matches('\0').to_string())
+            .filter(|value| !value.is_empty())
+            .collect::<Vec<_>>()
+    };
+    if info.title.is_none() {
+        info.title = values(StandardTagKey::TrackTitle).into_iter().next();
+    }
+    let artists = values(StandardTagKey::Artist);
+    if info.artist.is_none() && !artists.is_empty() {
+        info.artist = Some(artists.join(", "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn read(bytes: Vec<u8>) -> AudioInfo {
+        read_info(Box::new(Cursor::new(bytes)), &Hint::new()).unwrap()
+    }
+
+    fn vorbis_comment_block(comments: &[&str]) -> Vec<u8> {
+        let mut block = vec![];
+        for field in std::iter::once("vendor").chain(comments.iter().copied()) {
+            block.extend((field.len() as u32).to_le_bytes());
+            block.extend(field.as_bytes());
+            if field == "vendor" {
+                block.extend((comments.len() as u32).to_le_bytes());
+            }
+        }
+        block
+    }
+
+    /// One Ogg stream of `(granule, packets)` pages, with checksums, the first page marked as the
+    /// beginning of the stream and the last as its end.
+    fn ogg(pages: &[(u64, &[&[u8]])]) -> Vec<u8> {
+        let mut file = vec![];
+        for (sequence, (granule, packets)) in pages.iter().enumerate() {
+            let mut lacing = vec![];
+            for packet in *packets {
+                lacing.extend(std::iter::repeat_n(255, packet.len() / 255));
+                lacing.push((packet.len() % 255) as u8);
+            }
+            let first = if sequence == 0 { 0x02 } else { 0 };
+            let last = if sequence == pages.len() - 1 { 0x04 } else { 0 };
+            let mut page = b"OggS\0".to_vec();
+            page.push(first | last);
+            page.extend(granule.to_le_bytes());
+            page.extend(7u32.to_le_bytes());
+            page.extend((sequence as u32).to_le_bytes());
+            page.extend([0; 4]);
+            page.push(lacing.len() as u8);
+            page.extend(lacing);
+            packets.iter().for_each(|packet| page.extend(*packet));
+            let crc = page.iter().fold(0u32, |crc, &byte| {
+                (0..8).fold(crc ^ u32::from(byte) << 24, |crc, _| {
+                    match crc & 0x8000_0000 {
+                        0 => crc << 1,
+                        _ => crc << 1 ^ 0x04C1_1DB7,
+                    }
+                })
+            });
+            page[22..26].copy_from_slice(&crc.to_le_bytes());
+            file.extend(page);
+        }
+        file
+    }
+
+    /// Packs `(value, width)` fields the way Vorbis headers do, least significant bit first.
+    fn pack_bits(fields: &[(u32, u32)]) -> Vec<u8> {
+        let mut bytes = vec![];
+        let mut at = 0;
+        for &(value, width) in fields {
+            for bit in 0..width {
+                if at % 8 == 0 {
+                    bytes.push(0);
+                }
+                bytes[at / 8] |= ((value.checked_shr(bit).unwrap_or(0) & 1) as u8) << (at % 8);
+                at += 1;
+            }
+        }
+        bytes
+    }
+
+    fn id3v2(frames: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let syncsafe = |size: u32| {
+            (0..4)
+                .rev()
+                .map(move |shift| (size >> (shift * 7) & 0x7F) as u8)
+        };
+        let mut body = vec![];
+        for (id, text) in frames {
+            body.extend(*id);
+            body.extend(syncsafe(text.len() as u32));
+            body.extend([0, 0]);
+            body.extend(text);
+        }
+        let mut tag = b"ID3\x04\0\0".to_vec();
+        tag.extend(syncsafe(body.len() as u32));
+        tag.extend(body);
+        tag
+    }
+
+    #[test]
+    fn flac() {
+        let mut streaminfo = vec![0; 34];
+        // Blocks of 4096 samples at 44.1kHz, 2 channels, 16 bits and 90 seconds of samples.
+        streaminfo[..4].copy_from_slice(&[0x10, 0x00, 0x10, 0x00]);
+        streaminfo[10..13].copy_from_slice(&[0x0A, 0xC4, 0x42]);
+        streaminfo[13] = 0xF0;
+        streaminfo[14..18].copy_from_slice(&(44100u32 * 90).to_be_bytes());
+        let comments = vorbis_comment_block(&["title=Song", "ARTIST=Ann", "ARTIST=Bob"]);
+        // The header of the first frame, as described by the stream info, and its CRC-8.
+        let mut frame = vec![0xFF, 0xF8, 0xC9, 0x18, 0x00];
+        frame.push(frame.iter().fold(0u8, |crc, &byte| {
+            (0..8).fold(crc ^ byte, |crc, _| match crc & 0x80 {
+                0 => crc << 1,
+                _ => crc << 1 ^ 0x07,
+            })
+        }));
+
+        let mut file = b"fLaC".to_vec();
+        file.extend([0x00, 0, 0, 34]);
+        file.extend(streaminfo);
+        // Padding before the comments is skipped over.
+        file.extend([0x01, 0, 0, 3, 0, 0, 0]);
+        file.push(0x84);
+        file.extend(&(comments.len() as u32).to_be_bytes()[1..]);
+        file.extend(comments);
+        file.extend(frame);
+        file.extend([0; 64]);
+
+        assert_eq!(
+            read(file),
+            AudioInfo {
+                duration_seconds: Some(90.0),
+                title: Some("Song".to_string()),
+                artist: Some("Ann, Bob".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn ogg_vorbis() {
+        let mut identification = b"\x01vorbis".to_vec();
+        identification.extend([0, 0, 0, 0, 2]);
+        identification.extend(44100u32.to_le_bytes());
+        identification.extend([0; 12]);
+        // Blocks of 256 and 2048 samples, and the framing bit.
+        identification.extend([0xB8, 1]);
+        let mut comments = b"\x03vorbis".to_vec();
+        comments.extend(vorbis_comment_block(&["TITLE=歌", "ARTIST=知声"]));
+        // The smallest setup that is read through: one codebook, floor, residue, mapping and
+        // mode, all empty, and the framing bit.
+        let mut setup = b"\x05vorbis".to_vec();
+        setup.extend(pack_bits(&[
+            (0, 8),
+            (0x564342, 24),
+            (1, 16),
+            (1, 24),
+            (0, 1 + 1 + 5 + 4),
+            (0, 6 + 16),
+            (0, 6),
+            (1, 16),
+            (0, 5 + 2 + 4),
+            (0, 6 + 16 + 24 * 3 + 6 + 8 + 3 + 1),
+            (0, 6 + 16 + 1 + 1 + 2 + 8 * 3),
+            (0, 6 + 1 + 16 + 16 + 8),
+            (1, 1),
+        ]));
+
+        let file = ogg(&[
+            (0, &[&identification]),
+            (0, &[&comments, &setup]),
+            (0, &[&[0]]),
+            (44100 * 125, &[&[0]]),
+        ]);
+
+        assert_eq!(
+            read(file),
+            AudioInfo {
+                duration_seconds: Some(125.0),
+                title: Some("歌".to_string()),
+                artist: Some("知声".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn opus() {
+        let mut head = b"OpusHead".to_vec();
+        head.extend([1, 2]);
+        head.extend(312u16.to_le_bytes());
+        head.extend(44100u32.to_le_bytes());
+        head.extend([0; 3]);
+        let mut tags = b"OpusTags".to_vec();
+        tags.extend(vorbis_comment_block(&["Title=Song"]));
+
+        // Opus is never decoded, its duration comes from the granule of the last page.
+        let file = ogg(&[
+            (0, &[&head]),
+            (0, &[&tags]),
+            (0, &[&[0; 10]]),
+            (48000 * 60 + 312, &[&[0; 10]]),
+        ]);
+
+        let info = read(file);
+        assert_eq!(info.title.as_deref(), Some("Song"));
+        assert_eq!(info.artist, None);
+        let duration = info.duration_seconds.unwrap();
+        assert!((duration - 60.0).abs() < 0.1, "{duration}");
+    }
+
+    #[test]
+    fn mp3() {
+        // Text frames in UTF-8.
+        let text = |value: &str| [&[3], value.as_bytes(), &[0]].concat();
+        let mut file = id3v2(&[(b"TIT2", text("歌")), (b"TPE1", text("Ann"))]);
+        // MPEG 1 layer III frames at 128kbit/s and 44.1kHz, in stereo, for 10 seconds.
+        for _ in 0..383 {
+            let mut frame = vec![0; 417];
+            frame[..4].copy_from_slice(&[0xFF, 0xFB, 0x90, 0x00]);
+            file.extend(frame);
+        }
+
+        let info = read(file);
+        assert_eq!(info.title.as_deref(), Some("歌"));
+        assert_eq!(info.artist.as_deref(), Some("Ann"));
+        let duration = info.duration_seconds.unwrap();
+        assert!((duration - 10.0).abs() < 0.1, "{duration}");
+    }
+
+    #[test]
+    fn wav() {
+        let mut format = vec![1, 0, 2, 0];
+        format.extend(44100u32.to_le_bytes());
+        format.extend(176_400u32.to_le_bytes());
+        format.extend([4, 0, 16, 0]);
+        let mut list = b"INFO".to_vec();
+        for (id, text) in [(b"INAM", "Song"), (b"IART", "Ann"), (b"IART", "Bob")] {
+            let value = format!("{text}\0");
+            list.extend(id);
+            list.extend((value.len() as u32).to_le_bytes());
+            list.extend(value.as_bytes());
+            // Chunks are padded to an even length.
+            if value.len() % 2 == 1 {
+                list.push(0);
+            }
+        }
+
+        let mut chunks = b"WAVE".to_vec();
+        for (id, chunk) in [
+            (b"fmt ", format),
+            (b"LIST", list),
+            (b"data", vec![0; 176_400 * 3]),
+        ] {
+            chunks.extend(id);
+            chunks.extend((chunk.len() as u32).to_le_bytes());
+            chunks.extend(chunk);
+        }
+        let mut file = b"RIFF".to_vec();
+        file.extend((chunks.len() as u32).to_le_bytes());
+        file.extend(chunks);
+
+        assert_eq!(
+            read(file),
+            AudioInfo {
+                duration_seconds: Some(3.0),
+                title: Some("Song".to_string()),
+                artist: Some("Ann, Bob".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn not_audio() {
+        for bytes in [b"<svg></svg>".to_vec(), b"fLaC\x00\x00".to_vec()] {
+            assert!(read_info(Box::new(Cursor::new(bytes)), &Hint::new()).is_err());
+        }
+    }
+
+    #[test]
+    fn names_may_add_to_each_other() {
+        assert!(same_name("Song (feat. Bob)", "song"));
+        assert!(same_name("知声", " 知声 "));
+        assert!(!same_name("Song", "Another"));
+    }
+}
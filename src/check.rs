@@ -0,0 +1,469 @@
+use crate::publish::{ContentStatus, PublishAt, is_due};
+use crate::read::SiteContent;
+use crate::util::slugify;
+use camino::{Utf8Path, Utf8PathBuf};
+use chrono::{DateTime, FixedOffset};
+use log::{error, warn};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "エラー - error"),
+            Severity::Warning => write!(f, "警告 - warning"),
+        }
+    }
+}
+
+/// 1-based line and column in the whole markdown file, counted in characters.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file: Utf8PathBuf,
+    pub location: Option<Location>,
+    pub message: String,
+    /// The offending line with the span underlined.
+    pub snippet: Option<String>,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.location {
+            Some(location) => write!(f, "ファイル {}:{location}の内", self.file)?,
+            None => write!(f, "ファイル {}の内", self.file)?,
+        }
+        write!(f, ", {}: {}", self.severity, self.message)?;
+        if let Some(snippet) = &self.snippet {
+            write!(f, "\n{snippet}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Everything that is wrong with the content, collected so that it can all be reported at once.
+#[derive(Clone, Debug, Default)]
+pub struct Diagnostics {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn error(&mut self, file: impl AsRef<Utf8Path>, message: impl Into<String>) {
+        self.push(Severity::Error, file.as_ref(), None, None, message.into());
+    }
+
+    pub fn warning(&mut self, file: impl AsRef<Utf8Path>, message: impl Into<String>) {
+        self.push(Severity::Warning, file.as_ref(), None, None, message.into());
+    }
+
+    /// An error about `span` of `source`, the full text of `file`.
+    pub fn error_at(
+        &mut self,
+        file: impl AsRef<Utf8Path>,
+        source: &str,
+        span: Option<Range<usize>>,
+        message: impl Into<String>,
+    ) {
+        self.push_at(Severity::Error, file.as_ref(), source, span, message.into());
+    }
+
+    /// A warning about `span` of `source`, the full text of `file`.
+    pub fn warning_at(
+        &mut self,
+        file: impl AsRef<Utf8Path>,
+        source: &str,
+        span: Option<Range<usize>>,
+        message: impl Into<String>,
+    ) {
+        self.push_at(
+            Severity::Warning,
+            file.as_ref(),
+            source,
+            span,
+            message.into(),
+        );
+    }
+
+    fn push_at(
+        &mut self,
+        severity: Severity,
+        file: &Utf8Path,
+        source: &str,
+        span: Option<Range<usize>>,
+        message: String,
+    ) {
+        let span = span.map(|span| clamp(source, span));
+        let location = span.as_ref().map(|span| locate(source, span.start));
+        let snippet = span.map(|span| snippet(source, span));
+        self.push(severity, file, location, snippet, message);
+    }
+
+    fn push(
+        &mut self,
+        severity: Severity,
+        file: &Utf8Path,
+        location: Option<Location>,
+        snippet: Option<String>,
+        message: String,
+    ) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            file: file.to_path_buf(),
+            location,
+            message,
+            snippet,
+        });
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.diagnostics.len() - self.error_count()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+
+    /// Sends every diagnostic to the logger.
+    pub fn log(&self, build_id: u64) {
+        for diagnostic in &self.diagnostics {
+            match diagnostic.severity {
+                Severity::Error => error!("BUILD-{build_id}: {diagnostic}"),
+                Severity::Warning => warn!("BUILD-{build_id}: {diagnostic}"),
+            }
+        }
+    }
+
+    /// Sends every diagnostic to the logger as a warning, for checks that must not fail the build.
+    pub fn log_as_warnings(&self, build_id: u64) {
+        for diagnostic in &self.diagnostics {
+            warn!("BUILD-{build_id}: {diagnostic}");
+        }
+    }
+}
+
+/// Grouped by file and sorted by position, followed by a summary line.
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut by_file = BTreeMap::<&Utf8Path, Vec<&Diagnostic>>::new();
+        for diagnostic in &self.diagnostics {
+            by_file
+                .entry(&diagnostic.file)
+                .or_default()
+                .push(diagnostic);
+        }
+
+        for (file, mut diagnostics) in by_file {
+            diagnostics.sort_by_key(|diagnostic| (diagnostic.location, diagnostic.severity));
+            writeln!(f, "{file}:")?;
+            for diagnostic in diagnostics {
+                let message = diagnostic.message.replace('\n', "\n    ");
+                match diagnostic.location {
+                    Some(location) => {
+                        writeln!(f, "  {location} {}: {message}", diagnostic.severity)?
+                    }
+                    None => writeln!(f, "  {}: {message}", diagnostic.severity)?,
+                }
+                if let Some(snippet) = &diagnostic.snippet {
+                    for line in snippet.lines() {
+                        writeln!(f, "    {line}")?;
+                    }
+                }
+            }
+            writeln!(f)?;
+        }
+
+        write!(
+            f,
+            "{}件のエラー, {}件の警告 - {} error(s), {} warning(s)",
+            self.error_count(),
+            self.warning_count(),
+            self.error_count(),
+            self.warning_count()
+        )
+    }
+}
+
+/// Keeps a span inside `source` and on character boundaries.
+fn clamp(source: &str, span: Range<usize>) -> Range<usize> {
+    let floor = |mut index: usize| {
+        index = index.min(source.len());
+        while !source.is_char_boundary(index) {
+            index -= 1;
+        }
+        index
+    };
+    let start = floor(span.start);
+    start..floor(span.end).max(start)
+}
+
+fn locate(source: &str, offset: usize) -> Location {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    Location {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+    }
+}
+
+/// The line `span` starts on, underlined up to the end of the span or the line.
+fn snippet(source: &str, span: Range<usize>) -> String {
+    let line_start = source[..span.start]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1);
+    let line_end = source[span.start..]
+        .find('\n')
+        .map_or(source.len(), |newline| span.start + newline);
+    let line = source[line_start..line_end].trim_end_matches('\r');
+    let number = locate(source, span.start).line.to_string();
+
+    let padding = source[line_start..span.start].chars().count();
+    let width = source[span.start..span.end.min(line_end).max(span.start)]
+        .chars()
+        .count()
+        .max(1);
+    let gutter = " ".repeat(number.len());
+    format!(
+        "{number} | {line}\n{gutter} | {}{}",
+        " ".repeat(padding),
+        "^".repeat(width)
+    )
+}
+
+const NOT_A_MEMBER: &str =
+    "はメンバー中見つかりませんでした。 英語ネーム使うかどうか確認してください。";
+
+/// Checks that every member referenced by ascii name in the content actually exists, and that
+/// every track marked `on_site` has a work page to link to.
+pub fn check_references(
+    content: &SiteContent,
+    include_drafts: bool,
+    as_of: DateTime<FixedOffset>,
+    diagnostics: &mut Diagnostics,
+) {
+    let name_map = content.name_map();
+    let is_built = |status: ContentStatus, publish_at: Option<&PublishAt>| {
+        status.is_built(include_drafts) && is_due(publish_at, as_of)
+    };
+    // Whether each work is built, by title and author.
+    let works = content
+        .works
+        .iter()
+        .map(|work| {
+            let meta = &work.data.meta;
+            (
+                (meta.title.as_str(), meta.author.as_str()),
+                is_built(meta.status, meta.publish_at.as_ref()),
+            )
+        })
+        .collect::<HashMap<(&str, &str), bool>>();
+
+    for work in &content.works {
+        let meta = &work.data.meta;
+        check_slug(diagnostics, &work.file, meta.slug.as_deref());
+        check_member(
+            &name_map,
+            diagnostics,
+            &work.file,
+            "author",
+            &meta.author,
+            None,
+        );
+        for collaborator in &meta.collaborators {
+            check_member(
+                &name_map,
+                diagnostics,
+                &work.file,
+                "collaborators",
+                collaborator,
+                Some(&put_in("extra_collaborators")),
+            );
+        }
+    }
+
+    for album in &content.albums {
+        let meta = &album.data.meta;
+        let album_built = is_built(meta.status, meta.publish_at.as_ref());
+        check_slug(diagnostics, &album.file, meta.slug.as_deref());
+        for contributor in &meta.contributors {
+            check_member(
+                &name_map,
+                diagnostics,
+                &album.file,
+                "contributors",
+                contributor,
+                Some(&put_in("extra_contributors")),
+            );
+        }
+
+        if !meta.front_cover_illustrator_not_on_site {
+            check_member(
+                &name_map,
+                diagnostics,
+                &album.file,
+                "front_cover_illustrator",
+                &meta.front_cover_illustrator,
+                Some(&set_flag("front_cover_illustrator_not_on_site")),
+            );
+        }
+
+        for (header, illustration) in &meta.other_covers {
+            if !illustration.illustrator_is_not_on_site {
+                check_member(
+                    &name_map,
+                    diagnostics,
+                    &album.file,
+                    &format!("other_covers.{header}.illustrator"),
+                    &illustration.illustrator,
+                    Some(&set_flag("illustrator_is_not_on_site")),
+                );
+            }
+        }
+
+        for (index, track) in meta.tracklist.iter().enumerate() {
+            if !track.external_author {
+                check_member(
+                    &name_map,
+                    diagnostics,
+                    &album.file,
+                    &format!("tracklist[{index}].author"),
+                    &track.author,
+                    Some(&set_flag("external_author")),
+                );
+            }
+            if !track.on_site {
+                continue;
+            }
+            match works.get(&(track.title.as_str(), track.author.as_str())) {
+                None => diagnostics.error(
+                    &album.file,
+                    format!(
+                        "メタデータフィルド`tracklist[{index}].on_site`でエーラ発生: {} の「{}」という作品は見つかりませんでした。`title`と`author`が作品ページと一致するか確認してください。",
+                        track.author, track.title
+                    ),
+                ),
+                // The track would link to a page that is not there.
+                Some(false) if album_built => diagnostics.error(
+                    &album.file,
+                    format!(
+                        "メタデータフィルド`tracklist[{index}].on_site`でエーラ発生: {} の「{}」という作品は下書きか、まだ公開されていません。作品の`status`と`publish_at`を確認してください。 - The work \"{}\" by {} is a draft or not published yet, check its `status` and `publish_at`",
+                        track.author, track.title, track.title, track.author
+                    ),
+                ),
+                Some(_) => {}
+            }
+        }
+    }
+
+    for post in &content.news {
+        check_slug(diagnostics, &post.file, post.data.meta.slug.as_deref());
+        if let Some(author) = &post.data.meta.author {
+            check_member(&name_map, diagnostics, &post.file, "author", author, None);
+        }
+    }
+}
+
+/// Warns about a `slug` that cannot be used, which would be replaced with one from the title.
+fn check_slug(diagnostics: &mut Diagnostics, file: &Utf8Path, slug: Option<&str>) {
+    if let Some(slug) = slug
+        && slugify(slug).is_none()
+    {
+        diagnostics.warning(
+            file,
+            format!(
+                "メタデータフィルド`slug`でエーラ発生: 「{slug}」はURLにできません。英数字かかなで書いてください。題目から作ります。 - Slug `{slug}` cannot be made into a URL, write it in ascii or kana. Using the title instead"
+            ),
+        );
+    }
+}
+
+fn put_in(external_field: &str) -> String {
+    format!(
+        "投稿者が東大ボカロP同好会のメンバーじゃないければ、`{external_field}`で入れてください。"
+    )
+}
+
+fn set_flag(flag: &str) -> String {
+    format!("東大ボカロP同好会のメンバーじゃないければ、`{flag} = true`を設定してください。")
+}
+
+fn check_member(
+    name_map: &HashMap<String, String>,
+    diagnostics: &mut Diagnostics,
+    file: &Utf8Path,
+    field: &str,
+    ascii_name: &str,
+    hint: Option<&str>,
+) {
+    if name_map.contains_key(ascii_name) {
+        return;
+    }
+
+    let mut message =
+        format!("メタデータフィルド`{field}`でエーラ発生: {ascii_name} {NOT_A_MEMBER}");
+    if let Some(hint) = hint {
+        message.push_str(hint);
+    }
+    diagnostics.error(file, message);
+}
+
+/// Reports every output path that more than one page would be written to, naming the content
+/// file of each. `None` stands for a page not built from a content file, like an overview.
+pub fn check_unique_pages<'a>(
+    pages: impl IntoIterator<Item = (&'a Utf8Path, Option<&'a Utf8Path>)>,
+    diagnostics: &mut Diagnostics,
+) {
+    let mut by_path = BTreeMap::<&Utf8Path, Vec<Option<&Utf8Path>>>::new();
+    for (path, source) in pages {
+        by_path.entry(path).or_default().push(source);
+    }
+
+    for (path, sources) in by_path {
+        if sources.len() < 2 {
+            continue;
+        }
+        let names = sources
+            .iter()
+            .map(|source| match source {
+                Some(file) => file.to_string(),
+                None => "生成されたページ - a generated page".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let message = format!(
+            "ページ{path}は複数のファイルから作られます: {names}。 題目か`slug`を変えてください。 - The page {path} is built from more than one file: {names}. Change the title or `slug` of one of them."
+        );
+        let files = sources.iter().flatten().collect::<Vec<_>>();
+        if files.is_empty() {
+            diagnostics.error(path, message.clone());
+        }
+        for file in files {
+            diagnostics.error(file, message.clone());
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use anyhow::Context as _;
+use camino::Utf8Path;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// Name of the site configuration file, looked up in the data root.
+pub const CONFIG_FILE: &str = "vocadou.toml";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SiteConfig {
+    /// Where the site is hosted, e.g. `https://toudaivocadou.org`. `.` keeps links site-relative.
+    pub site_url: String,
+    /// Shown as `og:site_name`.
+    pub site_name: String,
+    /// Host for binaries too large for the repo, referred to as `miku:path/to/file`.
+    pub external_binary_url: String,
+    /// Social links shown in the footer of every page.
+    pub footer_links: Vec<String>,
+    /// Where the finished site ends up after a build.
+    pub output_dir: String,
+    /// Link to `/members/Mayday/` rather than `/members/Mayday.html`.
+    pub clean_urls: bool,
+    /// Days fetched embed data, e.g. Bluesky posts and thumbnails, is used before asking again.
+    pub embed_cache_days: u64,
+    /// Show a click-to-load placeholder instead of third-party players and posts, so they are
+    /// only loaded for visitors who ask for them.
+    pub embed_facades: bool,
+}
+
+impl Default for SiteConfig {
+    fn default() -> Self {
+        SiteConfig {
+            site_url: "https://toudaivocadou.org".to_string(),
+            site_name: "東京大学ボカロP同好会 - University of Tokyo Vocaloid Producer Club"
+                .to_string(), // production -> producer - ありがとーnekojitalter
+            external_binary_url: "https://miku.toudaivocadou.org".to_string(),
+            footer_links: vec!["https://x.com/toudaivocadou".to_string()],
+            output_dir: "dist".to_string(),
+            clean_urls: false,
+            embed_cache_days: 30,
+            embed_facades: true,
+        }
+    }
+}
+
+impl SiteConfig {
+    /// Reads [`CONFIG_FILE`] from the data root, falling back to the defaults if there is none.
+    pub fn load(data_root: &Utf8Path) -> Result<SiteConfig, anyhow::Error> {
+        let path = data_root.join(CONFIG_FILE);
+        if !path.exists() {
+            info!("No {path} found, using the default site configuration.");
+            return Ok(SiteConfig::default());
+        }
+
+        let text = fs::read_to_string(&path).with_context(|| format!("reading {path}"))?;
+        toml::from_str(&text).with_context(|| {
+            format!("設定ファイル {path} を読み込めませんでした - Failed to parse {path}")
+        })
+    }
+}
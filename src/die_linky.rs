@@ -49,12 +49,17 @@ impl FromStr for SocialLinkType {
             "twitter.com" => SocialLinkType::Twitter,
             "x.com" => SocialLinkType::Twitter,
             "bsky.app" => SocialLinkType::Bluesky,
-            "youtube.com" | "www.youtube.com" => SocialLinkType::Youtube,
-            "soundcloud.com" => SocialLinkType::Soundcloud,
-            "nicovideo.jp" | "www.nicovideo.jp" => SocialLinkType::NicoDouga,
+            "youtube.com" | "www.youtube.com" | "m.youtube.com" | "music.youtube.com"
+            | "youtu.be" => SocialLinkType::Youtube,
+            "soundcloud.com" | "www.soundcloud.com" | "m.soundcloud.com" | "on.soundcloud.com" => {
+                SocialLinkType::Soundcloud
+            }
+            "nicovideo.jp" | "www.nicovideo.jp" | "sp.nicovideo.jp" | "nico.ms" => {
+                SocialLinkType::NicoDouga
+            }
             "github.com" => SocialLinkType::Github,
             "linktree.com" | "linktr.ee" => SocialLinkType::LinkTree,
-            "spotify.com" => SocialLinkType::Spotify,
+            "spotify.com" | "open.spotify.com" => SocialLinkType::Spotify,
             "tiktok.com" => SocialLinkType::TikTok,
             "instagram.com" => SocialLinkType::Instagram,
             other => SocialLinkType::OtherUnknown(other.to_string()),
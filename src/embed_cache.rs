@@ -0,0 +1,185 @@
+use crate::external_links::unix_now;
+use anyhow::Context as _;
+use camino::{Utf8Path, Utf8PathBuf};
+use log::{info, warn};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Where answers from oEmbed endpoints and the like are kept, in the data root. Commit it to
+/// build the site without network, e.g. in CI.
+pub const EMBED_CACHE_FILE: &str = "embed-cache.json";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    /// Seconds since the Unix epoch.
+    fetched_at: u64,
+    value: serde_json::Value,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: BTreeMap<String, CacheEntry>,
+    /// Whether anything was fetched since loading, so an untouched file is left alone.
+    changed: bool,
+}
+
+/// Remote data embeds are built from, keyed by the URL it came from. Each URL is fetched at
+/// most once per build, and not again until `ttl` has passed. Pages asking for the same URL
+/// wait for one request, other URLs are fetched alongside it.
+#[derive(Debug)]
+pub struct EmbedCache {
+    path: Utf8PathBuf,
+    ttl: Duration,
+    /// Never fetch. Entries are used however old they are, and a miss is left to the caller.
+    offline: bool,
+    state: Mutex<CacheState>,
+    /// One lock per URL, held while it is fetched.
+    fetching: Mutex<HashMap<String, Arc<Mutex<()>>>>,
+}
+
+impl EmbedCache {
+    /// Reads the cache at `path`, starting afresh if there is none or it cannot be read.
+    pub fn load(path: &Utf8Path, ttl: Duration, offline: bool) -> EmbedCache {
+        let entries = match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|why| {
+                warn!("Ignoring unreadable embed cache {path}: {why}");
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+        EmbedCache {
+            path: path.to_path_buf(),
+            ttl,
+            offline,
+            state: Mutex::new(CacheState {
+                entries,
+                changed: false,
+            }),
+            fetching: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The value for `url`, fetched with `fetch` unless a fresh one is cached. `None` only when
+    /// offline with nothing cached.
+    pub fn get<T: Serialize + DeserializeOwned>(
+        &self,
+        url: &str,
+        fetch: impl FnOnce() -> Result<T, anyhow::Error>,
+    ) -> Result<Option<T>, anyhow::Error> {
+        let url_lock = self
+            .fetching
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner())
+            .entry(url.to_string())
+            .or_default()
+            .clone();
+        let _fetching = url_lock.lock().unwrap_or_else(|poison| poison.into_inner());
+
+        let now = unix_now();
+        let cached = self
+            .state
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner())
+            .entries
+            .get(url)
+            .cloned();
+        let fresh = cached
+            .as_ref()
+            .is_some_and(|entry| now.saturating_sub(entry.fetched_at) < self.ttl.as_secs());
+
+        let value = match cached {
+            Some(entry) if fresh || self.offline => entry.value,
+            None if self.offline => return Ok(None),
+            cached => match fetch() {
+                Ok(value) => {
+                    let value = serde_json::to_value(value)?;
+                    let mut state = self
+                        .state
+                        .lock()
+                        .unwrap_or_else(|poison| poison.into_inner());
+                    state.entries.insert(
+                        url.to_string(),
+                        CacheEntry {
+                            fetched_at: now,
+                            value: value.clone(),
+                        },
+                    );
+                    state.changed = true;
+                    value
+                }
+                Err(why) => match cached {
+                    Some(entry) => {
+                        warn!("Using the stale cached {url}, refreshing failed: {why:#}");
+                        entry.value
+                    }
+                    None => return Err(why),
+                },
+            },
+        };
+        serde_json::from_value(value)
+            .map(Some)
+            .with_context(|| format!("reading the cached {url}"))
+    }
+
+    /// Writes the cache back if anything new was fetched.
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner());
+        if !state.changed {
+            return Ok(());
+        }
+        info!("Saving the embed cache to {}.", self.path);
+        std::fs::write(
+            &self.path,
+            serde_json::to_string_pretty(&state.entries)? + "\n",
+        )
+        .with_context(|| format!("writing {}", self.path))?;
+        state.changed = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> EmbedCache {
+        EmbedCache::load(
+            Utf8Path::new("/nonexistent/embed-cache.json"),
+            Duration::from_secs(60),
+            false,
+        )
+    }
+
+    #[test]
+    fn fetches_each_url_once() {
+        let cache = cache();
+        let fetched = Mutex::new(0);
+        for _ in 0..2 {
+            let value = cache
+                .get("https://example.com/a", || {
+                    *fetched.lock().unwrap() += 1;
+                    Ok(1)
+                })
+                .unwrap();
+            assert_eq!(value, Some(1));
+        }
+        assert_eq!(*fetched.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn other_urls_are_not_held_up_by_a_fetch() {
+        let cache = cache();
+        let value = cache
+            .get("https://example.com/a", || {
+                Ok(cache.get("https://example.com/b", || Ok(2))?.unwrap() + 1)
+            })
+            .unwrap();
+        assert_eq!(value, Some(3));
+    }
+}
@@ -0,0 +1,530 @@
+use crate::check::Diagnostics;
+use crate::read::SiteContent;
+use anyhow::Context as _;
+use camino::{Utf8Path, Utf8PathBuf};
+use log::{info, warn};
+use reqwest::header::LOCATION;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/// Where results are kept between runs, in the data root like the embed cache and thumbnails.
+pub const CACHE_FILE: &str = ".cache/external-links.json";
+
+/// What a server answered for a URL, redirects not followed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkStatus {
+    pub status: u16,
+    /// Where a redirect points.
+    #[serde(default)]
+    pub location: Option<String>,
+}
+
+/// Asks a server about a URL. [`ReqwestClient`] does it for real.
+pub trait HttpClient: Sync {
+    fn status(&self, url: &str) -> Result<LinkStatus, anyhow::Error>;
+}
+
+pub struct ReqwestClient {
+    client: reqwest::blocking::Client,
+}
+
+impl ReqwestClient {
+    pub fn new() -> Result<ReqwestClient, anyhow::Error> {
+        let client = reqwest::blocking::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .timeout(Duration::from_secs(20))
+            .user_agent(concat!("toudaivocadou/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        Ok(ReqwestClient { client })
+    }
+}
+
+impl HttpClient for ReqwestClient {
+    fn status(&self, url: &str) -> Result<LinkStatus, anyhow::Error> {
+        let mut response = self.client.head(url).send()?;
+        // Plenty of servers refuse HEAD but answer GET just fine.
+        if matches!(response.status().as_u16(), 403 | 405 | 501) {
+            response = self.client.get(url).send()?;
+        }
+        Ok(LinkStatus {
+            status: response.status().as_u16(),
+            location: response
+                .headers()
+                .get(LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .map(str::to_string),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CheckOptions {
+    /// How many URLs are checked at once.
+    pub jobs: usize,
+    /// The least time between two requests to the same host.
+    pub per_host_interval: Duration,
+    /// How long a cached result is trusted.
+    pub ttl: Duration,
+    /// Only use cached results, never the network.
+    pub offline: bool,
+}
+
+/// A content file and the front matter field a URL was found in.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LinkSource {
+    pub file: Utf8PathBuf,
+    pub field: String,
+}
+
+/// Every external URL in the front matter of the content, with where each was found.
+pub fn external_links(content: &SiteContent) -> BTreeMap<String, Vec<LinkSource>> {
+    let mut links = BTreeMap::<String, Vec<LinkSource>>::new();
+    let mut add = |file: &Utf8Path, field: String, link: &str| {
+        if link.starts_with("http://") || link.starts_with("https://") {
+            links.entry(link.to_string()).or_default().push(LinkSource {
+                file: file.to_path_buf(),
+                field,
+            });
+        }
+    };
+
+    for member in &content.members {
+        for link in &member.data.meta.links {
+            add(&member.file, "links".to_string(), link);
+        }
+    }
+    for work in &content.works {
+        let meta = &work.data.meta;
+        if let Some(link) = &meta.link {
+            add(&work.file, "link".to_string(), link.as_str());
+        }
+        if let Some(link) = &meta.remix_original_work {
+            add(&work.file, "remix_original_work".to_string(), link);
+        }
+        for link in &meta.streaming {
+            add(&work.file, "streaming".to_string(), link);
+        }
+    }
+    for album in &content.albums {
+        let meta = &album.data.meta;
+        if let Some(link) = &meta.playlist_link {
+            add(&album.file, "playlist_link".to_string(), link);
+        }
+        if let Some(link) = &meta.crossfade_demonstration {
+            add(&album.file, "crossfade_demonstration".to_string(), link);
+        }
+        for (index, track) in meta.tracklist.iter().enumerate() {
+            if let Some(link) = &track.link {
+                add(&album.file, format!("tracklist[{index}].link"), link);
+            }
+        }
+        for link in &meta.sns_links {
+            add(&album.file, "sns_links".to_string(), link);
+        }
+    }
+    for post in &content.news {
+        for link in &post.data.meta.sns_links {
+            add(&post.file, "sns_links".to_string(), link.as_str());
+        }
+    }
+
+    for sources in links.values_mut() {
+        sources.sort();
+        sources.dedup();
+    }
+    links
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    #[serde(flatten)]
+    status: LinkStatus,
+    /// Seconds since the Unix epoch.
+    checked_at: u64,
+}
+
+/// Results of earlier checks, keyed by URL.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LinkCache {
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+impl LinkCache {
+    /// Reads the cache at `path`, starting afresh if there is none or it cannot be read.
+    pub fn load(path: &Utf8Path) -> LinkCache {
+        let Ok(text) = std::fs::read_to_string(path) else {
+            return LinkCache::default();
+        };
+        serde_json::from_str(&text).unwrap_or_else(|why| {
+            warn!("Ignoring unreadable link cache {path}: {why}");
+            LinkCache::default()
+        })
+    }
+
+    pub fn save(&self, path: &Utf8Path) -> Result<(), anyhow::Error> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("creating {parent}"))?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("writing {path}"))
+    }
+
+    fn fresh(&self, url: &str, ttl: Duration, now: u64) -> Option<&LinkStatus> {
+        self.entries
+            .get(url)
+            .filter(|entry| now.saturating_sub(entry.checked_at) < ttl.as_secs())
+            .map(|entry| &entry.status)
+    }
+
+    fn insert(&mut self, url: String, status: LinkStatus, now: u64) {
+        self.entries.insert(
+            url,
+            CacheEntry {
+                status,
+                checked_at: now,
+            },
+        );
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
+}
+
+/// Hands out turns so that no host gets two requests closer together than the interval.
+struct HostLimiter {
+    interval: Duration,
+    next_turn: Mutex<HashMap<String, Instant>>,
+}
+
+impl HostLimiter {
+    fn wait(&self, url: &str) {
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_default();
+        let turn = {
+            let mut next_turn = self.next_turn.lock().unwrap();
+            let now = Instant::now();
+            let turn = next_turn.get(&host).map_or(now, |&turn| turn.max(now));
+            next_turn.insert(host, turn + self.interval);
+            turn
+        };
+        std::thread::sleep(turn.saturating_duration_since(Instant::now()));
+    }
+}
+
+/// Checks every URL in `links`, from `cache` where the result is still fresh, and reports on the
+/// files they were found in: dead links as errors, and redirects and anything else odd as
+/// warnings.
+pub fn check_external_links(
+    links: &BTreeMap<String, Vec<LinkSource>>,
+    client: &dyn HttpClient,
+    cache: &mut LinkCache,
+    options: &CheckOptions,
+    diagnostics: &mut Diagnostics,
+) {
+    let now = unix_now();
+    let mut results = BTreeMap::<&str, Result<LinkStatus, String>>::new();
+    let mut to_fetch = vec![];
+    for url in links.keys() {
+        match cache.fresh(url, options.ttl, now) {
+            Some(status) => {
+                results.insert(url, Ok(status.clone()));
+            }
+            None if options.offline => {
+                results.insert(
+                    url,
+                    Err("オフラインなので確認していません - not checked while offline".to_string()),
+                );
+            }
+            None => to_fetch.push(url.as_str()),
+        }
+    }
+
+    info!(
+        "Checking {} external links, {} from the cache.",
+        links.len(),
+        links.len() - to_fetch.len()
+    );
+    let queue = Mutex::new(to_fetch.into_iter());
+    let fetched = Mutex::new(vec![]);
+    let limiter = HostLimiter {
+        interval: options.per_host_interval,
+        next_turn: Mutex::new(HashMap::new()),
+    };
+    std::thread::scope(|scope| {
+        for _ in 0..options.jobs.max(1) {
+            scope.spawn(|| {
+                loop {
+                    let Some(url) = queue.lock().unwrap().next() else {
+                        break;
+                    };
+                    limiter.wait(url);
+                    let result = client.status(url);
+                    fetched.lock().unwrap().push((url, result));
+                }
+            });
+        }
+    });
+
+    for (url, result) in fetched.into_inner().unwrap() {
+        match result {
+            Ok(status) => {
+                // Servers having a bad moment should be asked again next time.
+                if status.status < 500 && status.status != 429 {
+                    cache.insert(url.to_string(), status.clone(), now);
+                }
+                results.insert(url, Ok(status));
+            }
+            Err(why) => {
+                results.insert(url, Err(format!("{why:#}")));
+            }
+        }
+    }
+
+    for (url, result) in results {
+        for source in &links[url] {
+            let field = &source.field;
+            match &result {
+                Ok(LinkStatus { status: 404 | 410, .. }) => diagnostics.error(
+                    &source.file,
+                    format!(
+                        "`{field}`のリンク{url}は存在しません - Dead link {url} in `{field}`"
+                    ),
+                ),
+                Ok(LinkStatus { status, location }) if (300..400).contains(status) => diagnostics
+                    .warning(
+                        &source.file,
+                        format!(
+                            "`{field}`のリンク{url}は{}に移動しました ({status}) - Link {url} in `{field}` redirects to {0}",
+                            location.as_deref().unwrap_or("?")
+                        ),
+                    ),
+                Ok(LinkStatus { status, .. }) if *status >= 400 => diagnostics.warning(
+                    &source.file,
+                    format!(
+                        "`{field}`のリンク{url}はHTTP {status}を返しました - Link {url} in `{field}` answered HTTP {status}"
+                    ),
+                ),
+                Ok(_) => {}
+                Err(why) => diagnostics.warning(
+                    &source.file,
+                    format!(
+                        "`{field}`のリンク{url}を確認できませんでした - Could not check {url} in `{field}`: {why}"
+                    ),
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Answers `/ok` with 200, `/gone` with 404, `/moved` with a redirect to `/ok` and `/no-head`
+    /// with 405 to HEAD but 200 to GET, counting every request.
+    fn mock_server() -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let root = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(&stream);
+                reader.read_line(&mut request_line).unwrap();
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+                counter.fetch_add(1, Ordering::SeqCst);
+
+                let mut parts = request_line.split_whitespace();
+                let (method, path) = (parts.next().unwrap(), parts.next().unwrap());
+                let response = match (method, path) {
+                    (_, "/ok") | ("GET", "/no-head") => "200 OK\r\n".to_string(),
+                    (_, "/moved") => "301 Moved Permanently\r\nLocation: /ok\r\n".to_string(),
+                    ("HEAD", "/no-head") => "405 Method Not Allowed\r\n".to_string(),
+                    _ => "404 Not Found\r\n".to_string(),
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {response}Content-Length: 0\r\nConnection: close\r\n\r\n"
+                )
+                .unwrap();
+            }
+        });
+        (root, requests)
+    }
+
+    fn links(root: &str, paths: &[(&str, &str)]) -> BTreeMap<String, Vec<LinkSource>> {
+        let mut links = BTreeMap::<String, Vec<LinkSource>>::new();
+        for (file, path) in paths {
+            links
+                .entry(format!("{root}{path}"))
+                .or_default()
+                .push(LinkSource {
+                    file: Utf8PathBuf::from(*file),
+                    field: "links".to_string(),
+                });
+        }
+        links
+    }
+
+    fn options() -> CheckOptions {
+        CheckOptions {
+            jobs: 4,
+            per_host_interval: Duration::ZERO,
+            ttl: Duration::from_secs(60),
+            offline: false,
+        }
+    }
+
+    #[test]
+    fn reports_dead_links_and_redirects_by_file() {
+        let (root, _) = mock_server();
+        let links = links(
+            &root,
+            &[
+                ("members/a.md", "/ok"),
+                ("members/a.md", "/gone"),
+                ("works/b.md", "/moved"),
+                ("works/b.md", "/no-head"),
+            ],
+        );
+        let mut diagnostics = Diagnostics::default();
+        check_external_links(
+            &links,
+            &ReqwestClient::new().unwrap(),
+            &mut LinkCache::default(),
+            &options(),
+            &mut diagnostics,
+        );
+
+        assert_eq!(diagnostics.error_count(), 1);
+        assert_eq!(diagnostics.warning_count(), 1);
+        let report = diagnostics.to_string();
+        let (members, works) = report.split_once("works/b.md:").unwrap();
+        assert!(members.contains(&format!("Dead link {root}/gone")));
+        assert!(works.contains(&format!("Link {root}/moved in `links` redirects to /ok")));
+    }
+
+    #[test]
+    fn fresh_results_come_from_the_cache() {
+        let (root, requests) = mock_server();
+        let links = links(&root, &[("members/a.md", "/ok"), ("members/a.md", "/gone")]);
+        let client = ReqwestClient::new().unwrap();
+        let mut cache = LinkCache::default();
+
+        check_external_links(
+            &links,
+            &client,
+            &mut cache,
+            &options(),
+            &mut Diagnostics::default(),
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        let mut diagnostics = Diagnostics::default();
+        check_external_links(&links, &client, &mut cache, &options(), &mut diagnostics);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        assert_eq!(diagnostics.error_count(), 1);
+
+        let expired = CheckOptions {
+            ttl: Duration::ZERO,
+            ..options()
+        };
+        check_external_links(
+            &links,
+            &client,
+            &mut cache,
+            &expired,
+            &mut Diagnostics::default(),
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn offline_uses_only_the_cache() {
+        let (root, requests) = mock_server();
+        let links = links(&root, &[("members/a.md", "/gone")]);
+        let offline = CheckOptions {
+            offline: true,
+            ..options()
+        };
+        let mut diagnostics = Diagnostics::default();
+        check_external_links(
+            &links,
+            &ReqwestClient::new().unwrap(),
+            &mut LinkCache::default(),
+            &offline,
+            &mut diagnostics,
+        );
+
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+        assert_eq!(diagnostics.error_count(), 0);
+        assert_eq!(diagnostics.warning_count(), 1);
+    }
+
+    #[test]
+    fn requests_to_one_host_are_spaced_out() {
+        let (root, _) = mock_server();
+        let links = links(
+            &root,
+            &[
+                ("members/a.md", "/ok"),
+                ("members/a.md", "/moved"),
+                ("members/a.md", "/no-head"),
+            ],
+        );
+        let spaced = CheckOptions {
+            per_host_interval: Duration::from_millis(100),
+            ..options()
+        };
+        let start = Instant::now();
+        check_external_links(
+            &links,
+            &ReqwestClient::new().unwrap(),
+            &mut LinkCache::default(),
+            &spaced,
+            &mut Diagnostics::default(),
+        );
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn the_cache_survives_a_round_trip() {
+        let dir = std::env::temp_dir().join(format!("vocadou-link-cache-{}", std::process::id()));
+        let path = Utf8PathBuf::try_from(dir.join("links.json")).unwrap();
+        let mut cache = LinkCache::default();
+        cache.insert(
+            "https://example.com/".to_string(),
+            LinkStatus {
+                status: 200,
+                location: None,
+            },
+            unix_now(),
+        );
+        cache.save(&path).unwrap();
+
+        let loaded = LinkCache::load(&path);
+        std::fs::remove_dir_all(dir).unwrap();
+        assert!(
+            loaded
+                .fresh("https://example.com/", Duration::from_secs(60), unix_now())
+                .is_some()
+        );
+    }
+}
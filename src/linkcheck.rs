@@ -0,0 +1,115 @@
+use crate::check::Diagnostics;
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
+use hauchiwa::{Page, RuntimeError};
+use lol_html::{Settings, element, rewrite_str};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashSet};
+use url::Url;
+
+/// Where hauchiwa writes pages and assets.
+const DIST: &str = "dist";
+
+/// Checks every internal `href` and `src` in the finished HTML pages against the pages about to be
+/// written and the files already in `dist`, and reports the dead ones with the page they are on.
+/// Run as a post-build hook, after rewriting has put `site_url` in front of site-relative links.
+pub fn check_links(
+    pages: &[&Page],
+    site_url: &str,
+    diagnostics: &mut Diagnostics,
+) -> Result<(), RuntimeError> {
+    let written = pages
+        .iter()
+        .map(|page| page.path.as_path())
+        .collect::<HashSet<&Utf8Path>>();
+    let exists =
+        |target: &Utf8Path| written.contains(target) || Utf8Path::new(DIST).join(target).is_file();
+
+    for page in pages
+        .iter()
+        .filter(|page| page.path.extension() == Some("html"))
+    {
+        for link in links(&page.text)? {
+            let Some(target) = internal_target(&page.path, &link, site_url) else {
+                continue;
+            };
+            let found = if target.as_str().is_empty() || link_is_directory(&link) {
+                exists(&target.join("index.html"))
+            } else {
+                exists(&target) || exists(&target.join("index.html"))
+            };
+            if !found {
+                diagnostics.error(
+                    &page.path,
+                    format!("リンク先{link}は見つかりませんでした。 - Dead link to {link}"),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Every `href` and `src` in `html`, once each.
+fn links(html: &str) -> Result<BTreeSet<String>, RuntimeError> {
+    let links = RefCell::new(BTreeSet::new());
+    rewrite_str(
+        html,
+        Settings {
+            element_content_handlers: vec![
+                element!("[href]", |element| {
+                    links.borrow_mut().extend(element.get_attribute("href"));
+                    Ok(())
+                }),
+                element!("[src]", |element| {
+                    links.borrow_mut().extend(element.get_attribute("src"));
+                    Ok(())
+                }),
+            ],
+            ..Settings::new()
+        },
+    )
+    .map_err(|why| RuntimeError::msg(why.to_string()))?;
+    Ok(links.into_inner())
+}
+
+fn link_is_directory(link: &str) -> bool {
+    without_query(link).ends_with('/')
+}
+
+fn without_query(link: &str) -> &str {
+    link.split(['#', '?']).next().unwrap_or_default()
+}
+
+/// The output path `link` on the page at `page` leads to, or `None` if it leaves the site or
+/// only points into the page itself.
+fn internal_target(page: &Utf8Path, link: &str, site_url: &str) -> Option<Utf8PathBuf> {
+    let site_url = site_url.trim_end_matches('/');
+    let link = without_query(link);
+    if link.is_empty() {
+        return None;
+    }
+
+    let site_path = if site_url.contains("://")
+        && let Some(rest) = link.strip_prefix(site_url)
+        && (rest.is_empty() || rest.starts_with('/'))
+    {
+        rest.to_string()
+    } else if link.starts_with("//") || Url::parse(link).is_ok() {
+        return None;
+    } else if link.starts_with('/') {
+        link.to_string()
+    } else {
+        format!("/{}/{link}", page.parent().unwrap_or(Utf8Path::new("")))
+    };
+
+    let decoded = urlencoding::decode(&site_path).ok()?;
+    let mut target = Utf8PathBuf::new();
+    for component in Utf8Path::new(decoded.as_ref()).components() {
+        match component {
+            Utf8Component::Normal(part) => target.push(part),
+            // Climbing out of the site root lands nowhere, which the caller reports as dead.
+            Utf8Component::ParentDir if !target.pop() => return Some("..".into()),
+            _ => {}
+        }
+    }
+    Some(target)
+}
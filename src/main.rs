@@ -1,49 +1,71 @@
-use crate::album::AlbumMeta;
-use crate::member::MemberMeta;
-use crate::news::NewsMeta;
+use crate::audio_meta::read_audio_metadata;
+use crate::check::{Diagnostics, check_references, check_unique_pages};
+use crate::config::SiteConfig;
+use crate::embed_cache::{EMBED_CACHE_FILE, EmbedCache};
+use crate::external_links::{
+    CACHE_FILE, CheckOptions, LinkCache, ReqwestClient, check_external_links, external_links,
+};
+use crate::linkcheck::check_links;
+use crate::publish::PublishAt;
 use crate::read::{
-    parse_front_matter_and_fetch_contents, parse_post_meta, parse_work_meta, robots_txt,
+    ALBUMS_GLOB, MEMBERS_GLOB, POSTS_GLOB, SiteContent, WORKS_GLOB, load_source_file, robots_txt,
 };
+use crate::redirects::{check_redirects, redirects, redirects_file};
+use crate::routes::{Route, Routes};
+use crate::scaffold::NewContent;
+use crate::serve::{has_surfaced_errors, surface_errors};
 use crate::sitemap::SiteMap;
 use crate::templates::error::notfound;
-use crate::templates::functions::embed::{embed, jinja_embed};
+use crate::templates::functions::embed::{audio_player, embed, jinja_embed};
 use crate::templates::functions::member::jinja_member;
 use crate::templates::index::index;
 use crate::templates::join::join_vocadou;
 use crate::templates::members::{member_detail, members as member_overview};
-use crate::templates::news::{news_posts, post_detail, post_reference};
+use crate::templates::news::{news_posts, post_detail};
 use crate::templates::partials::navbar::Sections;
-use crate::templates::works::{
-    album_detail, album_reference, work_detail, work_reference, works as works_overview,
-};
+use crate::templates::redirect::redirect as redirect_page;
+use crate::templates::works::{album_detail, link_thumbnail, work_detail, works as works_overview};
+use crate::thumbnails::ThumbnailFetcher;
 use crate::util::{
-    AudioFile, SvgData, markup_to_page, render_metadata_and_final_page, rewrite_html, rewrite_link,
-    rewrite_page, rewrite_settings, set_external_bin_url, set_site_root, set_site_url, site_root,
+    SvgData, audio, load_audio, markup_to_page, render_metadata_and_final_page, rewrite_html,
+    rewrite_link, rewrite_page, rewrite_settings,
 };
-use crate::work::{DisplayWorkMeta, WorkMeta};
-use clap::{Parser, ValueEnum};
-use hauchiwa::RuntimeError;
-use hauchiwa::loader::Content;
-use hauchiwa::{Page, Website, loader};
-use log::{error, info};
+use crate::work::DisplayWorkMeta;
+use camino::{Utf8Path, Utf8PathBuf};
+use chrono::{DateTime, FixedOffset};
+use clap::{Parser, Subcommand};
+use hauchiwa::{Context, Hook, Page, RuntimeError, Website, loader};
+use log::info;
 use maud::{Render, html};
 use minijinja::Environment;
 use minijinja_contrib::add_to_environment;
 use minijinja_contrib::pycompat::unknown_method_callback;
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::time::Instant;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use url::Url;
 
 mod album;
+mod audio_meta;
+mod check;
+mod config;
 mod die_linky;
+mod embed_cache;
+mod external_links;
+mod linkcheck;
 mod member;
 mod metadata;
 mod news;
 mod optimize;
+mod publish;
 mod read;
+mod redirects;
+mod routes;
+mod scaffold;
+mod serve;
 mod sitemap;
 pub mod templates;
+mod thumbnails;
 mod util;
 mod work;
 
@@ -57,356 +79,807 @@ pub struct BuildData {
 #[derive(Parser, Debug, Clone)]
 #[command(version, about, long_about = None)]
 struct Args {
-    #[clap(short, long, default_value = "0")]
+    #[command(subcommand)]
+    mode: Option<Mode>,
+    #[clap(short, long, default_value = "0", global = true)]
     build_id: u64,
-    #[clap(short, long, default_value = ".")]
-    data_root: PathBuf,
-    #[clap(short, long, default_value = "https://miku.toudaivocadou.org")]
-    external_url_root: Url,
-    #[clap(short, long, default_value = "https://toudaivocadou.org")]
-    site_url: String,
+    /// Directory holding the content and `vocadou.toml`.
+    #[clap(short, long, default_value = ".", global = true)]
+    data_root: Utf8PathBuf,
+    /// Overrides `external_binary_url` from `vocadou.toml`.
+    #[clap(short, long, global = true)]
+    external_url_root: Option<Url>,
+    /// Overrides `site_url` from `vocadou.toml`.
+    #[clap(short, long, global = true)]
+    site_url: Option<String>,
+    /// Also build content with `status = "draft"`.
+    #[clap(long, global = true)]
+    include_drafts: bool,
+    /// Build the site as it will look at this date or date-time (JST), e.g. `2025-11-22` or
+    /// `2025-11-22T10:00:00`. Defaults to now.
+    #[clap(long, global = true)]
+    as_of: Option<PublishAt>,
+    /// Stay off the network and use only what is already cached.
+    #[clap(long, global = true)]
+    offline: bool,
 }
 
-#[derive(ValueEnum, Debug, Clone, Copy)]
+#[derive(Subcommand, Debug, Clone, Default)]
 enum Mode {
+    /// Build the site once into `dist`. This is the default.
+    #[default]
     Build,
+    /// Serve a preview on http://localhost:8080, rebuilding and reloading the browser on changes.
+    ///
+    /// Links are always kept site-relative here so the preview works locally.
+    #[command(name = "serve", alias = "watch")]
     Watch,
+    /// Validate all content and report every problem at once, without building anything.
+    Check,
+    /// Check the links in the content. Internal links are checked by every build already.
+    CheckLinks {
+        /// Check every external URL in the front matter, e.g. member `links` and `streaming`.
+        #[arg(long)]
+        external: bool,
+        /// How many URLs to check at once.
+        #[arg(long, default_value = "8")]
+        jobs: usize,
+        /// Least milliseconds between two requests to the same host.
+        #[arg(long, default_value = "1000")]
+        per_host_interval_ms: u64,
+        /// Days a cached result is trusted for.
+        #[arg(long, default_value = "7")]
+        ttl_days: u64,
+    },
+    /// Write a new content file with correctly formatted front matter.
+    New {
+        #[command(subcommand)]
+        kind: NewContent,
+    },
 }
 
 #[derive(Clone, Debug)]
 pub struct SiteData {
     pub build_id: u64,
-    pub site_url: String,
+    pub data_root: Utf8PathBuf,
+    pub config: SiteConfig,
+    pub include_drafts: bool,
+    /// Content with a later `publish_at` is left out.
+    pub as_of: DateTime<FixedOffset>,
+    pub routes: Routes,
+    /// Shared by every build in a `serve` session.
+    pub embed_cache: Arc<EmbedCache>,
+    /// Shared by every build in a `serve` session.
+    pub thumbnails: Arc<ThumbnailFetcher>,
 }
 
-pub fn build_site(build_id: u64, site_url: String) -> Result<(), hauchiwa::HauchiwaError> {
-    let site_data = SiteData { build_id, site_url };
+pub fn build_site(
+    mode: hauchiwa::Mode,
+    site_data: SiteData,
+) -> Result<(), hauchiwa::HauchiwaError> {
+    let build_id = site_data.build_id;
+    let output_dir = site_data.config.output_dir.clone();
+    let site_url = site_data.config.site_url.clone();
+    let embed_cache = site_data.embed_cache.clone();
+    let watching = matches!(mode, hauchiwa::Mode::Watch);
+    // hauchiwa wants its loader paths for the lifetime of the program
+    let site_root: &'static str = site_data.data_root.to_string().leak();
     info!("BUILD-{}: Configuring...", build_id);
     let mut website = Website::<SiteData>::config()
         .add_loaders([
             // load site content
-            loader::glob_content(
-                site_root(),
-                "members/[!_]*.md",
-                parse_front_matter_and_fetch_contents::<MemberMeta>,
-            ),
-            loader::glob_content(
-                site_root(),
-                "posts/[!_]*.md",
-                parse_post_meta,
-            ),
-            loader::glob_content(site_root(), "works/[!_]*.md", parse_work_meta),
-            loader::glob_content(
-                site_root(),
-                "albums/[!_]*.md",
-                parse_front_matter_and_fetch_contents::<AlbumMeta>,
-            ),
+            loader::glob_assets(site_root, MEMBERS_GLOB, load_source_file),
+            loader::glob_assets(site_root, POSTS_GLOB, load_source_file),
+            loader::glob_assets(site_root, WORKS_GLOB, load_source_file),
+            loader::glob_assets(site_root, ALBUMS_GLOB, load_source_file),
             // load CSS
-            loader::glob_styles(site_root(), "styles/*.css"),
+            loader::glob_styles(site_root, "styles/*.css"),
             // load JS
-            loader::glob_scripts(site_root(), "js/*.js"),
+            loader::glob_scripts(site_root, "js/*.js"),
             // load images
-            loader::glob_images(site_root(), "images/**/*.jpg"),
-            loader::glob_images(site_root(), "images/**/*.png"),
-            loader::glob_images(site_root(), "images/**/*.gif"),
-            loader::glob_images(site_root(), "images/**/*.avif"),
+            loader::glob_images(site_root, "images/**/*.jpg"),
+            loader::glob_images(site_root, "images/**/*.png"),
+            loader::glob_images(site_root, "images/**/*.gif"),
+            loader::glob_images(site_root, "images/**/*.avif"),
             // SVG assets require special treatment, we dont want processing
-            loader::glob_assets(site_root(), "assets/**/*.svg", |rt, data| {
+            loader::glob_assets(site_root, "assets/**/*.svg", |rt, data| {
                 let path = rt.store(&data, "svg")?;
                 Ok(SvgData { path, data })
             }),
-            loader::glob_assets(site_root(), "audio/**/*.ogg", |rt, data| {
-                rt.store(&data, "ogg")?;
-                Ok(AudioFile {})
-            })
+            // load audio
+            loader::glob_assets(site_root, "audio/**/*.mp3", |rt, data| {
+                load_audio(rt, data, "mp3")
+            }),
+            loader::glob_assets(site_root, "audio/**/*.ogg", |rt, data| {
+                load_audio(rt, data, "ogg")
+            }),
+            loader::glob_assets(site_root, "audio/**/*.opus", |rt, data| {
+                load_audio(rt, data, "opus")
+            }),
+            loader::glob_assets(site_root, "audio/**/*.flac", |rt, data| {
+                load_audio(rt, data, "flac")
+            }),
+            loader::glob_assets(site_root, "audio/**/*.wav", |rt, data| {
+                load_audio(rt, data, "wav")
+            }),
         ])
         .add_task("STATIC: build robots", |ctx| {
-            info!(
-                "BUILD-{}: Starting static build",
-                ctx.get_globals().data.build_id
-            );
-            let start_time = Instant::now();
-
-            let robots = robots_txt()?;
-            let index = markup_to_page(&ctx, "index.html", index(&ctx)?)?;
-            let notfound = markup_to_page(&ctx, "404.html", notfound(&ctx)?)?;
-            let join_vocadou = markup_to_page(&ctx, "join.html", join_vocadou(&ctx)?)?;
-
-            let time_taken = start_time.elapsed();
-            info!(
-                "BUILD-{}: Finished static build in {}s",
-                ctx.get_globals().data.build_id, time_taken.as_secs_f32()
-            );
-
-            Ok(vec![robots, index, notfound, join_vocadou])
+            surface_errors(&ctx, "static", build_static(&ctx))
         })
         .add_task("DYNAMIC: build all dynamic content", |ctx| {
-            info!(
-                "BUILD-{}: Starting dynamic content build",
-                ctx.get_globals().data.build_id
-            );
-            let start_time = Instant::now();
-
-            let members = ctx.glob_with_file::<Content<MemberMeta>>("members/[!_]*.md")?;
-            // construct name map
-            let member_ascii_to_name = members
-                .iter()
-                .map(|member_with_file| member_with_file.data)
-                .map(|member_meta| (member_meta.meta.ascii_name.clone(), member_meta.meta.name.clone()))
-                .collect::<HashMap<String, String>>();
-
-            let works = ctx.glob_with_file::<Content<WorkMeta>>("works/[!_]*.md")?;
-            info!(
-                "BUILD-{}: Ensuring all names exist in works.",
-                ctx.get_globals().data.build_id
-            );
-            for work in works.iter() {
-                let file_path = &work.file.file;
-                let work_meta = work.data;
-                if !member_ascii_to_name.contains_key(&work_meta.meta.author) {
-                    let error_str = format!("BUILD-{}: ファイル {}の内, メタデータフィルド`author`でエーラ発生: {} はメンバー中見つかりませんでした。 英語ネーム使うかどうか確認してください。", ctx.get_globals().data.build_id, file_path, &work_meta.meta.author);
-                    error!("{}", &error_str);
-                    return Err(RuntimeError::msg(error_str))
-                }
-                for collaborator in &work_meta.meta.collaborators {
-                    if !member_ascii_to_name.contains_key(collaborator) {
-                        let error_str = format!("BUILD-{}: ファイル {}の内, メタデータフィルド`collaborators`でエーラ発生: {} はメンバー中見つかりませんでした。 英語ネーム使うかどうか確認してください。投稿者が東大ボカロP同好会のメンバーじゃないければ、`extra_collaborators`で入れてください。", ctx.get_globals().data.build_id, file_path, &collaborator);
-                    error!("{}", &error_str);
-                        return Err(RuntimeError::msg(error_str))
-                    }
-                }
+            surface_errors(&ctx, "dynamic", build_dynamic(&ctx))
+        })
+        .add_hook(Hook::post_build(move |pages| {
+            if let Err(why) = embed_cache.save() {
+                log::warn!("Failed to save the embed cache: {why:#}");
             }
-
-            info!(
-                "BUILD-{}: Ensuring all names exist in albums.",
-                ctx.get_globals().data.build_id
-            );
-
-            let albums = ctx.glob_with_file::<Content<AlbumMeta>>("albums/[!_]*.md")?;
-            for album in &albums {
-                let file_path = &album.file.file;
-                let album_meta = album.data;
-                for contributor in &album_meta.meta.contributors {
-                    if !member_ascii_to_name.contains_key(contributor) {
-                        let error_str = format!("BUILD-{}: ファイル {}の内, メタデータフィルド`contributors`でエーラ発生: {} はメンバー中見つかりませんでした。 英語ネーム使うかどうか確認してください。投稿者が東大ボカロP同好会のメンバーじゃないければ、`extra_contributors`で入れてください。", ctx.get_globals().data.build_id, file_path, &contributor);
-                    error!("{}", &error_str);
-                        return Err(RuntimeError::msg(error_str))
-                    }
+            if !watching {
+                info!("BUILD-{}: Checking internal links.", build_id);
+                let mut diagnostics = Diagnostics::default();
+                check_links(pages, &site_url, &mut diagnostics)?;
+                diagnostics.log(build_id);
+                if diagnostics.has_errors() {
+                    return Err(RuntimeError::msg(diagnostics.to_string()));
                 }
+                return Ok(());
             }
 
-            info!(
-                "BUILD-{}: Ensuring all names exist in posts.",
-                ctx.get_globals().data.build_id
-            );
-
-            let news = ctx.glob_with_file::<Content<NewsMeta>>("posts/[!_]*.md")?;
-            for post in &news {
-                let file_path = &post.file.file;
-                let post_meta = post.data;
-
-                if let Some(author) = &post_meta.meta.author
-                    && !member_ascii_to_name.contains_key(author) {
-                        let error_str = format!("BUILD-{}: ファイル {}の内, メタデータフィルド`author`でエーラ発生: {} はメンバー中見つかりませんでした。 英語ネーム使うかどうか確認してください。", ctx.get_globals().data.build_id, file_path, author);
-                    error!("{}", &error_str);
-                        return Err(RuntimeError::msg(error_str))
-                    }
+            // A failed hook would stop `serve` on startup, or keep the error overlay from being
+            // written later. Pages left out by a failed task would only show up as dead links.
+            if has_surfaced_errors(pages) {
+                info!(
+                    "BUILD-{}: Skipping the internal link check, a task failed.",
+                    build_id
+                );
+                return Ok(());
+            }
+            info!("BUILD-{}: Checking internal links.", build_id);
+            let mut diagnostics = Diagnostics::default();
+            match check_links(pages, &site_url, &mut diagnostics) {
+                Ok(()) => diagnostics.log_as_warnings(build_id),
+                Err(why) => log::warn!("Failed to check internal links: {why}"),
             }
+            Ok(())
+        }))
+        .finish();
+    info!("BUILD-{}: Starting build...", build_id);
+    match mode {
+        hauchiwa::Mode::Build => {
+            website.build(site_data)?;
+            move_output(&output_dir).map_err(hauchiwa::BuildError::from)?;
+            Ok(())
+        }
+        hauchiwa::Mode::Watch => website.watch(site_data),
+    }
+}
 
-            info!(
-                "BUILD-{}: Finished all pre-build checks.",
-                ctx.get_globals().data.build_id
-            );
+/// hauchiwa always writes to `dist`, so move the result to where the config wants it.
+fn move_output(output_dir: &str) -> std::io::Result<()> {
+    let output_dir = Utf8Path::new(output_dir);
+    if output_dir == "dist" {
+        return Ok(());
+    }
+
+    info!("Moving dist to {output_dir}");
+    if output_dir.exists() {
+        std::fs::remove_dir_all(output_dir)?;
+    }
+    if let Some(parent) = output_dir.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::rename("dist", output_dir)
+}
 
+/// Pages written by [`build_static`], which the dynamic pages and aliases must stay clear of.
+fn static_pages(routes: &Routes) -> [Utf8PathBuf; 4] {
+    [
+        "robots.txt".into(),
+        routes.path(Route::Home),
+        routes.path(Route::NotFound),
+        routes.path(Route::Join),
+    ]
+}
 
-            info!(
-                "BUILD-{}: Starting rendering pages.",
-                ctx.get_globals().data.build_id
-            );
+/// Pages written by [`build_dynamic`] for `content`, with the file each is made from, so `check`
+/// can look for collisions without rendering anything.
+fn dynamic_pages<'a>(
+    content: &'a SiteContent,
+    routes: &Routes,
+) -> Vec<(Utf8PathBuf, Option<&'a Utf8Path>)> {
+    let members = content.members.iter().map(|member| {
+        let path = routes.path(Route::Member(&member.data.meta.ascii_name));
+        (path, Some(member.file.as_path()))
+    });
+    let works = content.works.iter().map(|work| {
+        let path = routes.path(Route::Work(&work.data.meta));
+        (path, Some(work.file.as_path()))
+    });
+    let albums = content.albums.iter().map(|album| {
+        let path = routes.path(Route::Album(&album.data.meta));
+        (path, Some(album.file.as_path()))
+    });
+    let posts = content.news.iter().map(|post| {
+        let path = routes.path(Route::Post(&post.data.meta));
+        (path, Some(post.file.as_path()))
+    });
+    let overviews = [
+        routes.path(Route::Members),
+        routes.path(Route::Works),
+        routes.path(Route::News),
+        "works_list.json".into(),
+    ]
+    .map(|path| (path, None));
+    members
+        .chain(works)
+        .chain(albums)
+        .chain(posts)
+        .chain(overviews)
+        .collect()
+}
 
-            info!(
-                "BUILD-{}: Construct: SiteMap.",
-                ctx.get_globals().data.build_id
-            );
+fn build_static(ctx: &Context<SiteData>) -> Result<Vec<Page>, RuntimeError> {
+    info!(
+        "BUILD-{}: Starting static build",
+        ctx.get_globals().data.build_id
+    );
+    let start_time = Instant::now();
+
+    let routes = &ctx.get_globals().data.routes;
+    let robots = robots_txt(&ctx.get_globals().data.data_root)?;
+    let index = markup_to_page(ctx, routes.path(Route::Home), index(ctx)?)?;
+    let notfound = markup_to_page(ctx, routes.path(Route::NotFound), notfound(ctx)?)?;
+    let join_vocadou = markup_to_page(ctx, routes.path(Route::Join), join_vocadou(ctx)?)?;
+
+    let time_taken = start_time.elapsed();
+    info!(
+        "BUILD-{}: Finished static build in {}s",
+        ctx.get_globals().data.build_id,
+        time_taken.as_secs_f32()
+    );
 
-            let mut sitemap = SiteMap {
-                members: members.iter().map(|member| { &member.data.meta }).cloned().collect(),
-                news: news.iter().map(|posts| &posts.data.meta).cloned().collect(),
-                works: works.iter().map(|works| &works.data.meta).cloned().collect(),
-                albums: albums.iter().map(|album| &album.data.meta).cloned().collect(),
-            };
-            sitemap.sort_self();
-            // TODO: add "worked on albums" and "posts". 
-
-            info!(
-                "BUILD-{}: Construct: minijinja Environment.",
-                ctx.get_globals().data.build_id
-            );
-
-            let mut environment = Environment::new();
-
-            // environment.add_function("sns_link", jinja_sns_icon);
-            environment.add_function("sns_embed", jinja_embed);
-            environment.add_function("member", jinja_member);
-            // environment.add_global("SITE", );
-            add_to_environment(&mut environment);
-            environment.set_unknown_method_callback(unknown_method_callback);
-
-            info!(
-                "BUILD-{}: Building member pages.",
-                ctx.get_globals().data.build_id
-            );
-            let mut member_overview = vec![Page::html("members.html", member_overview(&ctx, &sitemap).map_err(|why| why.context("Build Member Overview /members.html"))?.into_string())];
-            let mut member_detail = members.iter().map(|member_page| {
-                render_metadata_and_final_page(&ctx, &environment, &sitemap, &member_ascii_to_name, member_page.data, Sections::MemberProfile, &member_page.data.meta.ascii_name, format!("members/{}.html", &member_page.data.meta.ascii_name), |ctx, meta, sitemap, namemap, content| {
+    Ok(vec![robots, index, notfound, join_vocadou])
+}
+
+fn build_dynamic(ctx: &Context<SiteData>) -> Result<Vec<Page>, RuntimeError> {
+    info!(
+        "BUILD-{}: Starting dynamic content build",
+        ctx.get_globals().data.build_id
+    );
+    let start_time = Instant::now();
+
+    let mut diagnostics = Diagnostics::default();
+    let (
+        SiteContent {
+            members,
+            works,
+            albums,
+            news,
+        },
+        member_ascii_to_name,
+        mut redirects,
+    ) = {
+        let mut content = SiteContent::from_context(ctx, &mut diagnostics)?;
+        info!(
+            "BUILD-{}: Ensuring all referenced members exist.",
+            ctx.get_globals().data.build_id
+        );
+        let globals = &ctx.get_globals().data;
+        // Only new works while serving; the file watcher rebuilds once they are downloaded.
+        globals.thumbnails.fetch(globals, &content);
+        check_references(
+            &content,
+            globals.include_drafts,
+            globals.as_of,
+            &mut diagnostics,
+        );
+        read_audio_metadata(
+            &mut content,
+            &ctx.get_globals().data.data_root,
+            &mut diagnostics,
+        );
+        // Names of draft members are still needed by whatever mentions them.
+        let name_map = content.name_map();
+        content.retain_built(globals.include_drafts, globals.as_of);
+        let redirects = redirects(&content, &globals.routes);
+        (content, name_map, redirects)
+    };
+    diagnostics.log(ctx.get_globals().data.build_id);
+    if diagnostics.has_errors() {
+        return Err(RuntimeError::msg(diagnostics.to_string()));
+    }
+
+    info!(
+        "BUILD-{}: Finished all pre-build checks.",
+        ctx.get_globals().data.build_id
+    );
+
+    info!(
+        "BUILD-{}: Starting rendering pages.",
+        ctx.get_globals().data.build_id
+    );
+
+    info!(
+        "BUILD-{}: Construct: SiteMap.",
+        ctx.get_globals().data.build_id
+    );
+
+    let mut sitemap = SiteMap {
+        members: members
+            .iter()
+            .map(|member| &member.data.meta)
+            .filter(|member| member.status.is_listed())
+            .cloned()
+            .collect(),
+        news: news
+            .iter()
+            .map(|posts| &posts.data.meta)
+            .filter(|post| post.status.is_listed())
+            .cloned()
+            .collect(),
+        works: works
+            .iter()
+            .map(|works| &works.data.meta)
+            .filter(|work| work.status.is_listed())
+            .cloned()
+            .collect(),
+        albums: albums
+            .iter()
+            .map(|album| &album.data.meta)
+            .filter(|album| album.status.is_listed())
+            .cloned()
+            .collect(),
+    };
+    sitemap.sort_self();
+    // TODO: add "worked on albums" and "posts".
+
+    info!(
+        "BUILD-{}: Construct: minijinja Environment.",
+        ctx.get_globals().data.build_id
+    );
+
+    let mut environment = Environment::new();
+
+    // environment.add_function("sns_link", jinja_sns_icon);
+    let data = ctx.get_globals().data.clone();
+    environment.add_function("sns_embed", move |link: &str| jinja_embed(&data, link));
+    let routes = ctx.get_globals().data.routes.clone();
+    environment.add_function("member", move |ascii_name: &str| {
+        jinja_member(&routes, ascii_name)
+    });
+    // environment.add_global("SITE", );
+    add_to_environment(&mut environment);
+    environment.set_unknown_method_callback(unknown_method_callback);
+
+    info!(
+        "BUILD-{}: Building member pages.",
+        ctx.get_globals().data.build_id
+    );
+    let routes = &ctx.get_globals().data.routes;
+    let mut member_overview = vec![Page::html(
+        routes.path(Route::Members),
+        member_overview(ctx, &sitemap)
+            .map_err(|why| why.context("Build Member Overview /members.html"))?
+            .into_string(),
+    )];
+    let mut member_detail = members
+        .iter()
+        .map(|member| {
+            render_metadata_and_final_page(
+                ctx,
+                &environment,
+                &sitemap,
+                &member_ascii_to_name,
+                &member.data,
+                Sections::MemberProfile,
+                &member.data.meta.ascii_name,
+                routes.path(Route::Member(&member.data.meta.ascii_name)),
+                |ctx, meta, sitemap, namemap, content| {
                     member_detail(ctx, meta, sitemap, namemap, content)
-                })
-            }).collect::<Result<Vec<Page>, RuntimeError>>()?;
+                },
+            )
+        })
+        .collect::<Result<Vec<Page>, RuntimeError>>()?;
 
-            info!(
-                "BUILD-{}: Finished building member pages.",
-                ctx.get_globals().data.build_id
-            );
+    info!(
+        "BUILD-{}: Finished building member pages.",
+        ctx.get_globals().data.build_id
+    );
 
-            info!(
-                "BUILD-{}: Building work & album pages.",
-                ctx.get_globals().data.build_id
-            );
+    info!(
+        "BUILD-{}: Building work & album pages.",
+        ctx.get_globals().data.build_id
+    );
 
-            let mut works_overview = vec![Page::html("works.html", works_overview(&ctx, &sitemap, &member_ascii_to_name).map_err(|why| why.context("Build Works Overview works.html"))?.into_string())];
+    let mut works_overview = vec![Page::html(
+        routes.path(Route::Works),
+        works_overview(ctx, &sitemap, &member_ascii_to_name)
+            .map_err(|why| why.context("Build Works Overview works.html"))?
+            .into_string(),
+    )];
+
+    let mut works_detail = works
+        .iter()
+        .map(|work| {
+            render_metadata_and_final_page(
+                ctx,
+                &environment,
+                &sitemap,
+                &member_ascii_to_name,
+                &work.data,
+                Sections::WorksPost,
+                &work.data.meta.title,
+                routes.path(Route::Work(&work.data.meta)),
+                |ctx, meta, _, namemap, content| work_detail(ctx, meta, namemap, content),
+            )
+        })
+        .collect::<Result<Vec<Page>, RuntimeError>>()?;
 
-            let mut works_detail = works.iter().map(|work_page| {
-                render_metadata_and_final_page(&ctx, &environment, &sitemap, &member_ascii_to_name, work_page.data, Sections::WorksPost, &work_page.data.meta.title, format!("works/releases/{}.html", work_reference(&work_page.data.meta.title, &work_page.data.meta.author)), |ctx, meta, _, namemap, content| {
-                    work_detail(ctx, meta, namemap, content)
-                })
-            }).collect::<Result<Vec<Page>, RuntimeError>>()?;
+    info!(
+        "BUILD-{}: Finished building work pages.",
+        ctx.get_globals().data.build_id
+    );
 
-            info!(
-                "BUILD-{}: Finished building work pages.",
-                ctx.get_globals().data.build_id
-            );
+    let mut albums_detail = albums
+        .iter()
+        .map(|album| {
+            render_metadata_and_final_page(
+                ctx,
+                &environment,
+                &sitemap,
+                &member_ascii_to_name,
+                &album.data,
+                Sections::AlbumPost,
+                &album.data.meta.title,
+                routes.path(Route::Album(&album.data.meta)),
+                |ctx, meta, _sitemap, namemap, content| {
+                    album_detail(ctx, meta, namemap, content, &works)
+                },
+            )
+        })
+        .collect::<Result<Vec<Page>, RuntimeError>>()?;
 
-            let mut albums_detail = albums.iter().map(|album_page| {
-                render_metadata_and_final_page(&ctx, &environment, &sitemap, &member_ascii_to_name, album_page.data, Sections::AlbumPost, &album_page.data.meta.title, format!("works/albums/{}.html", album_reference(&album_page.data.meta.title, &album_page.data.meta.front_cover)), |ctx, meta, _, namemap, content| {
-                    album_detail(ctx, meta, namemap, content)
-                })
-            }).collect::<Result<Vec<Page>, RuntimeError>>()?;
+    info!(
+        "BUILD-{}: Finished building album pages.",
+        ctx.get_globals().data.build_id
+    );
 
-            info!(
-                "BUILD-{}: Finished building album pages.",
-                ctx.get_globals().data.build_id
-            );
+    info!(
+        "BUILD-{}: Building post pages.",
+        ctx.get_globals().data.build_id
+    );
 
-            info!(
-                "BUILD-{}: Building post pages.",
-                ctx.get_globals().data.build_id
-            );
+    let mut post_overview = vec![Page::html(
+        routes.path(Route::News),
+        news_posts(ctx, &sitemap, &member_ascii_to_name)?.into_string(),
+    )];
+
+    let mut posts_detail = news
+        .iter()
+        .map(|post| {
+            render_metadata_and_final_page(
+                ctx,
+                &environment,
+                &sitemap,
+                &member_ascii_to_name,
+                &post.data,
+                Sections::NewsPost,
+                &post.data.meta.title,
+                routes.path(Route::Post(&post.data.meta)),
+                |ctx, meta, _, namemap, content| post_detail(ctx, meta, content, namemap),
+            )
+        })
+        .collect::<Result<Vec<Page>, RuntimeError>>()?;
 
-            let mut post_overview = vec![Page::html("news.html", news_posts(&ctx, &sitemap, &member_ascii_to_name)?.into_string())];
+    info!(
+        "BUILD-{}: Finished building post pages.",
+        ctx.get_globals().data.build_id
+    );
 
-            let mut posts_detail = news.iter().map(|post_page| {
-                render_metadata_and_final_page(&ctx, &environment, &sitemap, &member_ascii_to_name, post_page.data, Sections::NewsPost, &post_page.data.meta.title, format!("news/{}.html", post_reference(&post_page.data.meta)), |ctx, meta, _, namemap, content| {
-                    post_detail(ctx, meta, content, namemap)
-                })
-            }).collect::<Result<Vec<Page>, RuntimeError>>()?;
-
-            info!(
-                "BUILD-{}: Finished building post pages.",
-                ctx.get_globals().data.build_id
-            );
-
-            info!(
-                "BUILD-{}: Building works_list.json",
-                ctx.get_globals().data.build_id
-            );
-            // TODO: search?
-            let works_list = works.iter().enumerate().map(|(id, work)| {
-                let work_meta = &work.data.meta;
-                let display_name = member_ascii_to_name.get(&work_meta.author).ok_or(anyhow::Error::msg("wtf???? coudlnt find member???".to_string()))?;
-                let alt_desc = work_meta.short.as_ref().unwrap_or(&work_meta.title);
-                let embedded_html = match &work_meta.display {
-                    work::CoverOrImage::Cover(cover) => {
-                        (html! {
-                            img href=(cover) alt=(alt_desc) {}
-                        }).into_string()
-                    },
-                    work::CoverOrImage::Link(url) => embed(url.as_str())?.render().into_string(),
-                    work::CoverOrImage::AudioFile(lnk) => embed(lnk)?.render().into_string(),
-                };
-                let fixed_html = rewrite_html(&embedded_html, rewrite_settings(&ctx.get_globals().data.site_url)).map_err(|why| anyhow::Error::msg(why.to_string()))?;
-                let site_url = &ctx.get_globals().data.site_url;
-                Ok(DisplayWorkMeta {
-                    id: id as i32,
-                    title: work_meta.title.clone(),
-                    description: work_meta.short.clone(),
-                    on_site_link: rewrite_link(site_url, format!("/works/releases/{}.html", work_reference(&work_meta.title, &work_meta.author)))?,
-                    author_displayname: display_name.clone(),
-                    author_link: rewrite_link(site_url, format!("/members/{}.html", work_meta.author))?,
-                    embed_html: fixed_html,
+    info!(
+        "BUILD-{}: Building works_list.json",
+        ctx.get_globals().data.build_id
+    );
+    // TODO: search?
+    let works_list = works
+        .iter()
+        .filter(|work| work.data.meta.status.is_listed())
+        .enumerate()
+        .map(|(id, work)| {
+            let work_meta = &work.data.meta;
+            let display_name =
+                member_ascii_to_name
+                    .get(&work_meta.author)
+                    .ok_or(anyhow::Error::msg(
+                        "wtf???? coudlnt find member???".to_string(),
+                    ))?;
+            let alt_desc = work_meta.short.as_ref().unwrap_or(&work_meta.title);
+            let embedded_html = match &work_meta.display {
+                work::CoverOrImage::Cover(cover) => (html! {
+                    img href=(cover) alt=(alt_desc) {}
                 })
-            }).collect::<Result<Vec<DisplayWorkMeta>, anyhow::Error>>().map_err(|why| {
-                RuntimeError::msg(why.to_string()).context("making works_list.json")
-            })?;
-            let works_list_serialize = serde_json::to_string(&works_list).map_err(|why| {
-                RuntimeError::msg(why.to_string()).context("serializing works_list.json")
-            })?;
-            let mut work_list_json = vec![Page::text("works_list.json", works_list_serialize)];
-            info!(
-                "BUILD-{}: Finished building works_list.json",
-                ctx.get_globals().data.build_id
-            );
-
-            info!(
-                "BUILD-{}: Collecting pages...",
-                ctx.get_globals().data.build_id
-            );
-
-            let all_lengths = member_overview.len() + member_detail.len() + works_overview.len() + works_detail.len() + albums_detail.len() + post_overview.len() + posts_detail.len() + work_list_json.len();
-            let mut all_pages = Vec::with_capacity(all_lengths);
-            all_pages.append(&mut member_overview);
-            all_pages.append(&mut member_detail);
-            all_pages.append(&mut works_overview);
-            all_pages.append(&mut works_detail);
-            all_pages.append(&mut albums_detail);
-            all_pages.append(&mut post_overview);
-            all_pages.append(&mut posts_detail);
-            all_pages.append(&mut work_list_json);
-
-            info!(
-                "BUILD-{}: Running final HTML rewrite.",
-                ctx.get_globals().data.build_id
-            );
-
-            let rewritten_pages = all_pages.into_iter().map(|page| {
-                rewrite_page(&ctx, page)
-            }).collect::<Result<Vec<Page>, RuntimeError>>()?;
-
-            let time_taken = start_time.elapsed();
-            info!(
-                "BUILD-{}: Finished build phase. {} pages, took {}s.",
-                ctx.get_globals().data.build_id, all_lengths, time_taken.as_secs_f32()
-            );
-
-            Ok(rewritten_pages)
+                .into_string(),
+                work::CoverOrImage::Link(url) => embed(
+                    &ctx.get_globals().data,
+                    url.as_str(),
+                    link_thumbnail(ctx, url.as_str())?.as_deref(),
+                    Some(&work_meta.title),
+                )?
+                .render()
+                .into_string(),
+                work::CoverOrImage::AudioFile(file) => {
+                    audio_player(&audio(ctx, file)?, &work_meta.title).into_string()
+                }
+            };
+            let fixed_html = rewrite_html(
+                &embedded_html,
+                rewrite_settings(&ctx.get_globals().data.config),
+            )
+            .map_err(|why| anyhow::Error::msg(why.to_string()))?;
+            let config = &ctx.get_globals().data.config;
+            Ok(DisplayWorkMeta {
+                id: id as i32,
+                title: work_meta.title.clone(),
+                description: work_meta.short.clone(),
+                on_site_link: rewrite_link(config, routes.url(Route::Work(work_meta)))?,
+                author_displayname: display_name.clone(),
+                author_link: rewrite_link(config, routes.url(Route::Member(&work_meta.author)))?,
+                embed_html: fixed_html,
+            })
         })
-        .finish();
-    info!("BUILD-{}: Starting build...", build_id);
-    website.build(site_data)
+        .collect::<Result<Vec<DisplayWorkMeta>, anyhow::Error>>()
+        .map_err(|why| RuntimeError::msg(why.to_string()).context("making works_list.json"))?;
+    let works_list_serialize = serde_json::to_string(&works_list)
+        .map_err(|why| RuntimeError::msg(why.to_string()).context("serializing works_list.json"))?;
+    let mut work_list_json = vec![Page::text("works_list.json", works_list_serialize)];
+    info!(
+        "BUILD-{}: Finished building works_list.json",
+        ctx.get_globals().data.build_id
+    );
+
+    info!(
+        "BUILD-{}: Ensuring no two pages share a path.",
+        ctx.get_globals().data.build_id
+    );
+    let mut diagnostics = Diagnostics::default();
+    let static_pages = static_pages(routes);
+    let page_sources = member_detail
+        .iter()
+        .zip(members.iter().map(|member| &member.file))
+        .chain(works_detail.iter().zip(works.iter().map(|work| &work.file)))
+        .chain(
+            albums_detail
+                .iter()
+                .zip(albums.iter().map(|album| &album.file)),
+        )
+        .chain(posts_detail.iter().zip(news.iter().map(|post| &post.file)))
+        .map(|(page, file)| (page.path.as_path(), Some(file.as_path())))
+        .chain(
+            member_overview
+                .iter()
+                .chain(&works_overview)
+                .chain(&post_overview)
+                .chain(&work_list_json)
+                .map(|page| (page.path.as_path(), None)),
+        )
+        .chain(static_pages.iter().map(|path| (path.as_path(), None)));
+    check_unique_pages(page_sources, &mut diagnostics);
+
+    info!(
+        "BUILD-{}: Collecting pages...",
+        ctx.get_globals().data.build_id
+    );
+
+    let all_lengths = member_overview.len()
+        + member_detail.len()
+        + works_overview.len()
+        + works_detail.len()
+        + albums_detail.len()
+        + post_overview.len()
+        + posts_detail.len()
+        + work_list_json.len();
+    let mut all_pages = Vec::with_capacity(all_lengths);
+    all_pages.append(&mut member_overview);
+    all_pages.append(&mut member_detail);
+    all_pages.append(&mut works_overview);
+    all_pages.append(&mut works_detail);
+    all_pages.append(&mut albums_detail);
+    all_pages.append(&mut post_overview);
+    all_pages.append(&mut posts_detail);
+    all_pages.append(&mut work_list_json);
+
+    info!(
+        "BUILD-{}: Building redirects from aliases and old page names.",
+        ctx.get_globals().data.build_id
+    );
+    check_redirects(
+        &mut redirects,
+        all_pages
+            .iter()
+            .map(|page| page.path.as_path())
+            .chain(static_pages.iter().map(Utf8PathBuf::as_path)),
+        &mut diagnostics,
+    );
+    diagnostics.log(ctx.get_globals().data.build_id);
+    if diagnostics.has_errors() {
+        return Err(RuntimeError::msg(diagnostics.to_string()));
+    }
+    let config = &ctx.get_globals().data.config;
+    let link = |to: &str| rewrite_link(config, to.to_string());
+    for redirect in &redirects {
+        let to = link(&redirect.to)
+            .map_err(|why| RuntimeError::msg(why.to_string()).context("making redirects"))?;
+        all_pages.push(Page::html(
+            redirect.path(),
+            redirect_page(&to).into_string(),
+        ));
+    }
+    all_pages.push(Page::text(
+        "_redirects",
+        redirects_file(&redirects, link)
+            .map_err(|why| RuntimeError::msg(why.to_string()).context("making _redirects"))?,
+    ));
+
+    info!(
+        "BUILD-{}: Running final HTML rewrite.",
+        ctx.get_globals().data.build_id
+    );
+
+    let rewritten_pages = all_pages
+        .into_iter()
+        .map(|page| rewrite_page(ctx, page))
+        .collect::<Result<Vec<Page>, RuntimeError>>()?;
+
+    let time_taken = start_time.elapsed();
+    info!(
+        "BUILD-{}: Finished build phase. {} pages, took {}s.",
+        ctx.get_globals().data.build_id,
+        rewritten_pages.len(),
+        time_taken.as_secs_f32()
+    );
+
+    Ok(rewritten_pages)
+}
+
+fn check(
+    data_root: &Utf8Path,
+    routes: &Routes,
+    include_drafts: bool,
+    as_of: DateTime<FixedOffset>,
+) -> ! {
+    let mut diagnostics = Diagnostics::default();
+    let mut content =
+        SiteContent::from_disk(data_root, &mut diagnostics).expect("Failed to read content!");
+    check_references(&content, include_drafts, as_of, &mut diagnostics);
+    read_audio_metadata(&mut content, data_root, &mut diagnostics);
+
+    // The pages and redirects the build would write, checked the same way.
+    content.retain_built(include_drafts, as_of);
+    let pages = dynamic_pages(&content, routes);
+    let static_pages = static_pages(routes);
+    check_unique_pages(
+        pages
+            .iter()
+            .map(|(path, file)| (path.as_path(), *file))
+            .chain(static_pages.iter().map(|path| (path.as_path(), None))),
+        &mut diagnostics,
+    );
+    check_redirects(
+        &mut redirects(&content, routes),
+        pages
+            .iter()
+            .map(|(path, _)| path.as_path())
+            .chain(static_pages.iter().map(Utf8PathBuf::as_path)),
+        &mut diagnostics,
+    );
+
+    println!("{diagnostics}");
+    std::process::exit(if diagnostics.has_errors() { 1 } else { 0 })
+}
+
+fn check_external(data_root: &Utf8Path, external: bool, options: CheckOptions) -> ! {
+    if !external {
+        eprintln!(
+            "内部リンクはビルドの度に確認されます。外部リンクは --external で確認してください。 - Internal links are checked by every build, use --external to check external links."
+        );
+        std::process::exit(2)
+    }
+
+    let mut diagnostics = Diagnostics::default();
+    let content =
+        SiteContent::from_disk(data_root, &mut diagnostics).expect("Failed to read content!");
+    let client = ReqwestClient::new().expect("Failed to set up the HTTP client!");
+    let cache_file = data_root.join(CACHE_FILE);
+    let mut cache = LinkCache::load(&cache_file);
+    check_external_links(
+        &external_links(&content),
+        &client,
+        &mut cache,
+        &options,
+        &mut diagnostics,
+    );
+    if let Err(why) = cache.save(&cache_file) {
+        log::warn!("Failed to save the link cache: {why:#}");
+    }
+
+    println!("{diagnostics}");
+    std::process::exit(if diagnostics.has_errors() { 1 } else { 0 })
+}
+
+fn new(data_root: &Utf8Path, routes: &Routes, kind: NewContent) -> ! {
+    match scaffold::new_content(data_root, routes, kind) {
+        Ok(path) => {
+            println!("{path} を作成しました - Created {path}");
+            std::process::exit(0)
+        }
+        Err(why) => {
+            eprintln!("{why:#}");
+            std::process::exit(1)
+        }
+    }
 }
 
 fn main() {
     env_logger::init();
     let args = Args::parse();
-    set_external_bin_url(args.external_url_root.to_string());
-    set_site_root(
-        // args.data_root
-        //     .to_str()
-        //     .expect("Invalid SITE_ROOT path!")
-        //     .to_string(),
-        "a".to_string(),
-    );
-    // set_site_url(args.site_url.to_string());
-    set_site_url(".".to_string());
 
-    build_site(args.build_id, ".".to_string()).expect("Failed to build site!")
+    let mut config = SiteConfig::load(&args.data_root).expect("Failed to load site configuration!");
+    if let Some(site_url) = args.site_url {
+        config.site_url = site_url;
+    }
+    if let Some(external_url_root) = args.external_url_root {
+        config.external_binary_url = external_url_root.to_string();
+    }
+
+    let mode = match args.mode.unwrap_or_default() {
+        Mode::Check => check(
+            &args.data_root,
+            &Routes::new(&config),
+            args.include_drafts,
+            publish::as_of(args.as_of),
+        ),
+        Mode::CheckLinks {
+            external,
+            jobs,
+            per_host_interval_ms,
+            ttl_days,
+        } => check_external(
+            &args.data_root,
+            external,
+            CheckOptions {
+                jobs,
+                per_host_interval: Duration::from_millis(per_host_interval_ms),
+                ttl: Duration::from_secs(ttl_days * 24 * 60 * 60),
+                offline: args.offline,
+            },
+        ),
+        Mode::New { kind } => new(&args.data_root, &Routes::new(&config), kind),
+        Mode::Build => hauchiwa::Mode::Build,
+        Mode::Watch => {
+            config.site_url = ".".to_string();
+            hauchiwa::Mode::Watch
+        }
+    };
+
+    let routes = Routes::new(&config);
+    let embed_cache = EmbedCache::load(
+        &args.data_root.join(EMBED_CACHE_FILE),
+        Duration::from_secs(config.embed_cache_days * 24 * 60 * 60),
+        args.offline,
+    );
+    let site_data = SiteData {
+        build_id: args.build_id,
+        data_root: args.data_root,
+        config,
+        include_drafts: args.include_drafts,
+        as_of: publish::as_of(args.as_of),
+        routes,
+        embed_cache: Arc::new(embed_cache),
+        thumbnails: Arc::new(ThumbnailFetcher::new(args.offline)),
+    };
+    // Before the build, so the image loader finds them. Problems with the content itself are
+    // left for the build to report.
+    if let Ok(content) = SiteContent::from_disk(&site_data.data_root, &mut Diagnostics::default()) {
+        site_data.thumbnails.fetch(&site_data, &content);
+    }
+    build_site(mode, site_data).expect("Failed to build site!")
 }
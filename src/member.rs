@@ -1,4 +1,6 @@
 use crate::metadata::Metadata;
+use crate::publish::{ContentStatus, PublishAt};
+use crate::routes::{Route, Routes};
 use crate::templates::partials::navbar::Sections;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
@@ -14,10 +16,17 @@ pub struct MemberMeta {
     pub short: String,              // 自己紹介（短い）
 
     pub links: HashSet<String>, // SNSリンク
+
+    #[serde(default)]
+    pub status: ContentStatus, // 公開状態
+    #[serde(default)]
+    pub publish_at: Option<PublishAt>, // 公開日時 (JST)
+    #[serde(default)]
+    pub aliases: Vec<String>, // 古いURL (転送されます)
 }
 
 impl MemberMeta {
-    pub fn to_metadata(value: MemberMeta) -> Metadata {
+    pub fn to_metadata(value: MemberMeta, routes: &Routes) -> Metadata {
         let page_title = if value.name == value.ascii_name {
             value.name.clone()
         } else {
@@ -27,11 +36,12 @@ impl MemberMeta {
         Metadata {
             page_title: format!("{page_title} - 東京大学ボカロP同好会"),
             page_image: Some(format!("images/icon/{}.jpg", value.ascii_name)),
-            canonical_link: format!("/members/{}.html", value.ascii_name),
+            canonical_link: routes.url(Route::Member(&value.ascii_name)),
             section: Sections::MemberProfile,
             description: Some(value.short),
             author: Some(value.name),
             date: None,
+            status: value.status,
         }
     }
 }
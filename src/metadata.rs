@@ -1,25 +1,39 @@
 use crate::SiteData;
+use crate::publish::ContentStatus;
 use crate::templates::partials::navbar::Sections;
-use crate::util::image;
+use crate::util::{image, slash_guard};
 use hauchiwa::{Context, RuntimeError};
 use maud::{Markup, html};
 use serde::{Deserialize, Serialize};
+use url::Url;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Metadata {
     pub page_title: String,
     pub page_image: Option<String>,
+    /// Site-absolute link to the page, made into a full URL by [`canonical_url`].
     pub canonical_link: String,
     pub section: Sections,
     pub description: Option<String>,
     pub author: Option<String>,
     pub date: Option<String>,
+    pub status: ContentStatus,
+}
+
+/// The page's URL under the configured site URL, which is absolute unless the site is built for
+/// local preview with a site URL of `.`.
+pub fn canonical_url(sack: &Context<SiteData>, metadata: &Metadata) -> String {
+    slash_guard(
+        &sack.get_globals().data.config.site_url,
+        &metadata.canonical_link,
+    )
 }
 
 pub fn render_metadata(
     sack: &Context<SiteData>,
     metadata: &Metadata,
 ) -> Result<Markup, RuntimeError> {
+    let config = &sack.get_globals().data.config;
     let page_type = match metadata.section {
         Sections::Home => "website",
         Sections::Members => "website",
@@ -46,33 +60,59 @@ pub fn render_metadata(
         _ => html! {},
     };
 
-    let canonical_link = &metadata.canonical_link;
+    let canonical_link = canonical_url(sack, metadata);
 
     let image_lnk = metadata
         .page_image
         .as_ref()
         .map(|img| {
-            if img.starts_with("https://") || img.ends_with(".webp") {
+            if is_web_url(img) || img.ends_with(".webp") {
                 Ok(img.clone())
             } else {
                 image(sack, img)
             }
         })
-        .map_or(Ok(None), |v| v.map(Some))?;
+        .map_or(Ok(None), |v| v.map(Some))?
+        // Pictures on the site are linked site-absolute, but crawlers want a full URL.
+        .map(|img| match is_web_url(&img) {
+            true => img,
+            false => slash_guard(&config.site_url, &img),
+        });
 
     Ok(html! {
         title { (&metadata.page_title) }
         meta property="og:title" content=(&metadata.page_title);
-        meta property="og:url" content=(canonical_link);
+        meta property="og:url" content=(&canonical_link);
         meta property="og:type" content=(page_type);
-        meta property="og:site_name" content="東京大学ボカロP同好会 - University of Tokyo Vocaloid Producer Club"; // production -> producer - ありがとーnekojitalter
+        meta property="og:site_name" content=(&config.site_name);
         meta property="og:locale" content="ja_JP";
         @if let Some(img) = &image_lnk {
             meta property="og:image" content=(img);
         }
+        @if !metadata.status.is_indexed() {
+            meta name="robots" content="noindex";
+        }
         @if let Some(desc) = &metadata.description {
             meta property="og:description" content=(desc);
         }
         (others)
     })
 }
+
+/// Whether `link` is a full http(s) URL rather than a path on the site. `miku:` links are not.
+fn is_web_url(link: &str) -> bool {
+    Url::parse(link).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn web_urls_are_left_as_they_are() {
+        assert!(is_web_url("https://i.ytimg.com/vi/x/hqdefault.jpg"));
+        assert!(is_web_url("http://example.com/cover.png"));
+        assert!(!is_web_url("/images/cover.webp"));
+        assert!(!is_web_url("miku:images/cover.png"));
+    }
+}
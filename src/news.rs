@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use toml::value::Date;
 use url::Url;
 
+use crate::publish::{ContentStatus, PublishAt};
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct NewsMeta {
     pub title: String,
@@ -16,6 +18,15 @@ pub struct NewsMeta {
 
     #[serde(default)]
     pub sns_links: Vec<Url>,
+
+    #[serde(default)]
+    pub status: ContentStatus,
+    #[serde(default)]
+    pub publish_at: Option<PublishAt>,
+    #[serde(default)]
+    pub slug: Option<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -32,4 +43,13 @@ pub struct RawNewsMeta {
 
     #[serde(default)]
     pub sns_links: Vec<Url>,
+
+    #[serde(default)]
+    pub status: ContentStatus,
+    #[serde(default)]
+    pub publish_at: Option<PublishAt>,
+    #[serde(default)]
+    pub slug: Option<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
 }
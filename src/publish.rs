@@ -0,0 +1,115 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de, ser};
+use std::str::FromStr;
+use toml::value::{Datetime, Offset, Time};
+
+/// Whether a piece of content is shown, set with `status` in its front matter.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentStatus {
+    #[default]
+    Published,
+    /// Built, but left out of every listing and kept away from search engines.
+    Unlisted,
+    /// Only built with `--include-drafts`, and marked as such on the page.
+    Draft,
+}
+
+impl ContentStatus {
+    pub fn is_built(self, include_drafts: bool) -> bool {
+        self != ContentStatus::Draft || include_drafts
+    }
+
+    /// Drafts are listed when they are built at all, so that previews look like the real thing.
+    pub fn is_listed(self) -> bool {
+        self != ContentStatus::Unlisted
+    }
+
+    pub fn is_indexed(self) -> bool {
+        self == ContentStatus::Published
+    }
+}
+
+/// Japan Standard Time, which `publish_at` and `--as-of` are read in unless they give an offset.
+fn jst() -> FixedOffset {
+    FixedOffset::east_opt(9 * 60 * 60).expect("+09:00 is a valid offset")
+}
+
+/// When a piece of content goes public, set with `publish_at` in its front matter. Written as a
+/// TOML date or date-time, e.g. `2025-11-22` or `2025-11-22T10:00:00`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PublishAt(pub DateTime<FixedOffset>);
+
+impl TryFrom<Datetime> for PublishAt {
+    type Error = String;
+
+    fn try_from(datetime: Datetime) -> Result<Self, Self::Error> {
+        let Some(date) = datetime.date else {
+            return Err(format!(
+                "日付がありません - `{datetime}` has no date, write it as e.g. 2025-11-22T10:00:00"
+            ));
+        };
+        let time = datetime.time.unwrap_or(Time {
+            hour: 0,
+            minute: 0,
+            second: 0,
+            nanosecond: 0,
+        });
+        let offset = match datetime.offset {
+            None => jst(),
+            Some(Offset::Z) => FixedOffset::east_opt(0).expect("UTC is a valid offset"),
+            Some(Offset::Custom { minutes }) => FixedOffset::east_opt(i32::from(minutes) * 60)
+                .ok_or_else(|| format!("`{datetime}` has an invalid offset"))?,
+        };
+
+        NaiveDate::from_ymd_opt(date.year.into(), date.month.into(), date.day.into())
+            .and_then(|date| {
+                date.and_hms_nano_opt(
+                    time.hour.into(),
+                    time.minute.into(),
+                    time.second.into(),
+                    time.nanosecond,
+                )
+            })
+            .and_then(|naive| offset.from_local_datetime(&naive).single())
+            .map(PublishAt)
+            .ok_or_else(|| format!("`{datetime}` is not a valid date-time"))
+    }
+}
+
+impl FromStr for PublishAt {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let datetime = text
+            .parse::<Datetime>()
+            .map_err(|why| format!("`{text}`: {why}"))?;
+        PublishAt::try_from(datetime)
+    }
+}
+
+impl<'de> Deserialize<'de> for PublishAt {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        PublishAt::try_from(Datetime::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for PublishAt {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0
+            .to_rfc3339()
+            .parse::<Datetime>()
+            .map_err(ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+/// The moment a build shows the site as of: `--as-of` if given, otherwise now.
+pub fn as_of(as_of: Option<PublishAt>) -> DateTime<FixedOffset> {
+    as_of.map_or_else(|| Utc::now().with_timezone(&jst()), |as_of| as_of.0)
+}
+
+/// Whether content is public yet, given its `publish_at`.
+pub fn is_due(publish_at: Option<&PublishAt>, as_of: DateTime<FixedOffset>) -> bool {
+    publish_at.is_none_or(|publish_at| publish_at.0 <= as_of)
+}
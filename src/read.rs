@@ -1,58 +1,555 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
+use std::ops::Range;
 use std::str::FromStr;
 
-use crate::FRONT_MATTER_SPLIT;
+use crate::album::AlbumMeta;
+use crate::check::Diagnostics;
+use crate::member::MemberMeta;
 use crate::news::{NewsMeta, RawNewsMeta};
+use crate::publish::is_due;
+use crate::util::AUDIO_EXTENSIONS;
 use crate::work::{CoverOrImage, RawWorkMeta, WorkMeta};
-use hauchiwa::Page;
-use hauchiwa::RuntimeError;
+use crate::{FRONT_MATTER_SPLIT, SiteData};
+use camino::{Utf8Path, Utf8PathBuf};
+use chrono::{DateTime, FixedOffset};
+use hauchiwa::loader::{Content, Runtime};
+use hauchiwa::{Context, Page, RuntimeError};
 use serde::de::DeserializeOwned;
 
+/// Raw text of a content markdown file.
+///
+/// Content is loaded unparsed and only parsed inside the build tasks. That way hauchiwa tracks the
+/// file before its front matter is looked at, so a broken file still triggers a rebuild in
+/// `serve` once it is fixed.
+pub struct SourceFile {
+    pub text: String,
+}
+
+pub fn load_source_file(_: Runtime, data: Vec<u8>) -> Result<SourceFile, anyhow::Error> {
+    Ok(SourceFile {
+        text: String::from_utf8(data)?,
+    })
+}
+
+/// A parsed content file, along with the path it was loaded from.
+pub struct ContentFile<T: Send + Sync + 'static> {
+    pub file: Utf8PathBuf,
+    pub data: Content<T>,
+}
+
+pub const MEMBERS_GLOB: &str = "members/[!_]*.md";
+pub const WORKS_GLOB: &str = "works/[!_]*.md";
+pub const ALBUMS_GLOB: &str = "albums/[!_]*.md";
+pub const POSTS_GLOB: &str = "posts/[!_]*.md";
+
+/// Every piece of content on the site.
+pub struct SiteContent {
+    pub members: Vec<ContentFile<MemberMeta>>,
+    pub works: Vec<ContentFile<WorkMeta>>,
+    pub albums: Vec<ContentFile<AlbumMeta>>,
+    pub news: Vec<ContentFile<NewsMeta>>,
+}
+
+impl SiteContent {
+    /// Parses the content loaded by hauchiwa. Files that fail to parse are left out and reported
+    /// in `diagnostics`.
+    pub fn from_context(
+        ctx: &Context<SiteData>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<SiteContent, RuntimeError> {
+        Ok(SiteContent {
+            members: parse_loaded(
+                ctx,
+                MEMBERS_GLOB,
+                parse_front_matter_and_fetch_contents::<MemberMeta>,
+                diagnostics,
+            )?,
+            works: parse_loaded(ctx, WORKS_GLOB, parse_work_meta, diagnostics)?,
+            albums: parse_loaded(
+                ctx,
+                ALBUMS_GLOB,
+                parse_front_matter_and_fetch_contents::<AlbumMeta>,
+                diagnostics,
+            )?,
+            news: parse_loaded(ctx, POSTS_GLOB, parse_post_meta, diagnostics)?,
+        })
+    }
+
+    /// Like [`SiteContent::from_context`], but reads the files straight from the data root.
+    pub fn from_disk(
+        site_root: &Utf8Path,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<SiteContent, anyhow::Error> {
+        let parse_all = |pattern| -> Result<_, anyhow::Error> {
+            let mut sources = vec![];
+            for path in glob::glob(site_root.join(pattern).as_str())? {
+                let path = Utf8PathBuf::try_from(path?)?;
+                let text = std::fs::read_to_string(&path)?;
+                sources.push((path, text));
+            }
+            Ok(sources)
+        };
+
+        Ok(SiteContent {
+            members: parse_content_files(
+                parse_all(MEMBERS_GLOB)?,
+                parse_front_matter_and_fetch_contents::<MemberMeta>,
+                diagnostics,
+            ),
+            works: parse_content_files(parse_all(WORKS_GLOB)?, parse_work_meta, diagnostics),
+            albums: parse_content_files(
+                parse_all(ALBUMS_GLOB)?,
+                parse_front_matter_and_fetch_contents::<AlbumMeta>,
+                diagnostics,
+            ),
+            news: parse_content_files(parse_all(POSTS_GLOB)?, parse_post_meta, diagnostics),
+        })
+    }
+
+    /// Drops drafts unless they are wanted, and anything whose `publish_at` is still to come.
+    pub fn retain_built(&mut self, include_drafts: bool, as_of: DateTime<FixedOffset>) {
+        self.members.retain(|member| {
+            let meta = &member.data.meta;
+            meta.status.is_built(include_drafts) && is_due(meta.publish_at.as_ref(), as_of)
+        });
+        self.works.retain(|work| {
+            let meta = &work.data.meta;
+            meta.status.is_built(include_drafts) && is_due(meta.publish_at.as_ref(), as_of)
+        });
+        self.albums.retain(|album| {
+            let meta = &album.data.meta;
+            meta.status.is_built(include_drafts) && is_due(meta.publish_at.as_ref(), as_of)
+        });
+        self.news.retain(|post| {
+            let meta = &post.data.meta;
+            meta.status.is_built(include_drafts) && is_due(meta.publish_at.as_ref(), as_of)
+        });
+    }
+
+    /// Member ascii names to display names.
+    pub fn name_map(&self) -> HashMap<String, String> {
+        self.members
+            .iter()
+            .map(|member| {
+                (
+                    member.data.meta.ascii_name.clone(),
+                    member.data.meta.name.clone(),
+                )
+            })
+            .collect()
+    }
+}
+
+fn parse_loaded<T>(
+    ctx: &Context<SiteData>,
+    pattern: &str,
+    parse: fn(&str) -> Result<Parsed<T>, SourceIssue>,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<ContentFile<T>>, RuntimeError>
+where
+    T: Send + Sync + 'static,
+{
+    let sources = ctx
+        .glob_with_file::<SourceFile>(pattern)?
+        .into_iter()
+        .map(|source| (source.file.file.clone(), source.data.text.clone()));
+    Ok(parse_content_files(sources, parse, diagnostics))
+}
+
+fn parse_content_files<T>(
+    sources: impl IntoIterator<Item = (Utf8PathBuf, String)>,
+    parse: fn(&str) -> Result<Parsed<T>, SourceIssue>,
+    diagnostics: &mut Diagnostics,
+) -> Vec<ContentFile<T>>
+where
+    T: Send + Sync + 'static,
+{
+    sources
+        .into_iter()
+        .filter_map(|(file, source)| match parse(&source) {
+            Ok(parsed) => {
+                for warning in parsed.warnings {
+                    diagnostics.warning_at(&file, &source, warning.span, warning.message);
+                }
+                Some(ContentFile {
+                    file,
+                    data: Content {
+                        meta: parsed.meta,
+                        text: parsed.content,
+                    },
+                })
+            }
+            Err(why) => {
+                diagnostics.error_at(
+                    &file,
+                    &source,
+                    why.span,
+                    format!(
+                        "ファイルを読み込めませんでした - Failed to read file: {}",
+                        why.message
+                    ),
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+/// Something wrong with a content file, along with the bytes of the file it is about if known.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceIssue {
+    pub span: Option<Range<usize>>,
+    pub message: String,
+}
+
+impl SourceIssue {
+    pub fn new(span: Option<Range<usize>>, message: impl Into<String>) -> SourceIssue {
+        SourceIssue {
+            span,
+            message: message.into(),
+        }
+    }
+}
+
+/// Metadata and markdown of a content file, plus anything suspicious found on the way.
+pub struct Parsed<T> {
+    pub meta: T,
+    pub content: String,
+    pub warnings: Vec<SourceIssue>,
+}
+
+/// How the front matter of a content file is written.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FrontMatterFormat {
+    /// Fenced by `+++` lines, or the older style ended by a `===` line.
+    Toml,
+    /// Fenced by `---` lines.
+    Yaml,
+}
+
+const TOML_FENCE: &str = "+++";
+const YAML_FENCE: &str = "---";
+
+/// The front matter of a content file, and where in the file it starts.
+pub struct FrontMatter<'a> {
+    pub format: FrontMatterFormat,
+    pub text: &'a str,
+    pub offset: usize,
+    pub content: &'a str,
+}
+
+/// Splits a content file into its front matter and markdown. `+++` and `---` fences are only
+/// recognised on the first line; otherwise everything up to the first `===` line is front matter.
+pub fn split_front_matter(file: &str) -> Result<FrontMatter<'_>, SourceIssue> {
+    let start = if file.starts_with('\u{feff}') {
+        '\u{feff}'.len_utf8()
+    } else {
+        0
+    };
+    let first_line = file[start..]
+        .split_inclusive('\n')
+        .next()
+        .unwrap_or_default();
+
+    let (format, marker, offset) = match first_line.trim() {
+        TOML_FENCE => (
+            FrontMatterFormat::Toml,
+            TOML_FENCE,
+            start + first_line.len(),
+        ),
+        YAML_FENCE => (
+            FrontMatterFormat::Yaml,
+            YAML_FENCE,
+            start + first_line.len(),
+        ),
+        _ => (FrontMatterFormat::Toml, FRONT_MATTER_SPLIT, start),
+    };
+
+    match split_at_line(&file[offset..], marker) {
+        Some((text, content)) => Ok(FrontMatter {
+            format,
+            text,
+            offset,
+            content,
+        }),
+        None => Err(SourceIssue::new(
+            None,
+            format!(
+                "Failed to split front matter! Ensure that the front matter splitter \"{marker}\" exists on its own line! - フロントデータを分離できませんでした。フロントデータ分離マーカー「{marker}」が一行にあるのかを確認してください！"
+            ),
+        )),
+    }
+}
+
+/// Splits `text` around the first line that is only `marker`.
+fn split_at_line<'a>(text: &'a str, marker: &str) -> Option<(&'a str, &'a str)> {
+    let mut start = 0;
+    for line in text.split_inclusive('\n') {
+        if line.trim() == marker {
+            return Some((&text[..start], &text[start + line.len()..]));
+        }
+        start += line.len();
+    }
+    None
+}
+
 pub fn parse_front_matter_and_fetch_contents<Metadata>(
     file: &str,
-) -> Result<(Metadata, String), anyhow::Error>
+) -> Result<Parsed<Metadata>, SourceIssue>
 where
     Metadata: DeserializeOwned,
 {
-    let (front_matter, content) = match file.split_once(FRONT_MATTER_SPLIT) {
-        Some(v) => v,
-        None => {
-            return Err(anyhow::Error::msg(format!(
-                "Failed to split front matter! Ensure that the front matter splitter \"{FRONT_MATTER_SPLIT}\" exists! - フロントデータを分離できませんでした。フロントデータ分離マーカー「{FRONT_MATTER_SPLIT}」があるのかを確認してください！"
-            )));
+    let front_matter = split_front_matter(file)?;
+    let offset = front_matter.offset;
+
+    let (meta, table) = match front_matter.format {
+        FrontMatterFormat::Toml => {
+            let located = |why: toml::de::Error| {
+                SourceIssue::new(
+                    why.span()
+                        .map(|span| span.start + offset..span.end + offset),
+                    why.message(),
+                )
+            };
+            let meta = toml::from_str::<Metadata>(front_matter.text).map_err(located)?;
+            let table = toml::from_str::<toml::Table>(front_matter.text).map_err(located)?;
+            (meta, table)
+        }
+        FrontMatterFormat::Yaml => {
+            let yaml = serde_yaml::from_str(front_matter.text).map_err(|why| {
+                let span = why
+                    .location()
+                    .map(|location| location.index() + offset..location.index() + offset + 1);
+                // Line numbers in the message are counted from the start of the front matter.
+                let lines_before = file[..offset].matches('\n').count();
+                let message = why
+                    .to_string()
+                    .split(" at line ")
+                    .enumerate()
+                    .map(|(index, part)| {
+                        let digits = part.len()
+                            - part.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+                        match part[..digits].parse::<usize>() {
+                            Ok(line) if index > 0 => {
+                                format!(" at line {}{}", line + lines_before, &part[digits..])
+                            }
+                            _ if index > 0 => format!(" at line {part}"),
+                            _ => part.to_string(),
+                        }
+                    })
+                    .collect::<String>();
+                SourceIssue::new(span, message)
+            })?;
+            let toml::Value::Table(mut table) = yaml_to_toml(yaml) else {
+                return Err(SourceIssue::new(
+                    None,
+                    "フロントデータはマッピングではありません - The front matter is not a mapping",
+                ));
+            };
+            for field in DATE_FIELDS {
+                if let Some(toml::Value::String(string)) = table.get(field)
+                    && let Ok(datetime) = string.parse::<toml::value::Datetime>()
+                {
+                    table.insert(field.to_string(), toml::Value::Datetime(datetime));
+                }
+            }
+            // Spans into the converted TOML would be meaningless, so only the message is kept.
+            let meta = toml::to_string(&table)
+                .map_err(|why| why.to_string())
+                .and_then(|toml| {
+                    toml::from_str::<Metadata>(&toml).map_err(|why| why.message().to_string())
+                })
+                .map_err(|message| SourceIssue::new(None, message))?;
+            (meta, table)
         }
     };
 
-    let toml_parsed = toml::from_str::<Metadata>(front_matter)?;
+    Ok(Parsed {
+        meta,
+        content: front_matter.content.to_string(),
+        warnings: unknown_fields::<Metadata>(&table, front_matter.text, offset),
+    })
+}
+
+/// Fields `#[serde(default)]` would otherwise silently ignore, most likely misspelled.
+fn unknown_fields<Metadata>(
+    table: &toml::Table,
+    front_matter: &str,
+    offset: usize,
+) -> Vec<SourceIssue>
+where
+    Metadata: DeserializeOwned,
+{
+    let known = field_names::<Metadata>();
+    table
+        .keys()
+        .filter(|key| !known.contains(&key.as_str()))
+        .map(|key| {
+            let mut message = format!(
+                "不明なフィールド`{key}`は無視されます - Unknown field `{key}` is ignored."
+            );
+            let suggestion = known
+                .iter()
+                .map(|field| (strsim::damerau_levenshtein(key, field), field))
+                .filter(|(distance, field)| *distance <= (field.len() / 3).max(2))
+                .min();
+            if let Some((_, field)) = suggestion {
+                message.push_str(&format!(
+                    " `{field}`のことですか？ - Did you mean `{field}`?"
+                ));
+            }
+            let span =
+                key_span(front_matter, key).map(|span| span.start + offset..span.end + offset);
+            SourceIssue::new(span, message)
+        })
+        .collect()
+}
+
+/// Where a top level key is written, for both `key = ...`/`key: ...` and `[key]` style tables.
+fn key_span(front_matter: &str, key: &str) -> Option<Range<usize>> {
+    let mut start = 0;
+    for line in front_matter.split_inclusive('\n') {
+        let indent = line.len() - line.trim_start().len();
+        let rest = line.trim_start().trim_start_matches('[');
+        let brackets = line.trim_start().len() - rest.len();
+        let quoted = rest.starts_with(['"', '\'']) as usize;
+        if let Some(after) = rest[quoted..].strip_prefix(key)
+            && after
+                .trim_start_matches(['"', '\''])
+                .trim_start()
+                .starts_with(['=', ':', '.', ']'])
+        {
+            let key_start = start + indent + brackets + quoted;
+            return Some(key_start..key_start + key.len());
+        }
+        start += line.len();
+    }
+    None
+}
+
+/// The field names a struct deserializes from, found by asking it to deserialize itself.
+pub fn field_names<T: DeserializeOwned>() -> &'static [&'static str] {
+    use serde::de::{self, Visitor};
+
+    struct FieldNames(&'static [&'static str]);
+
+    impl<'de> de::Deserializer<'de> for &mut FieldNames {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _: &'static str,
+            fields: &'static [&'static str],
+            _: V,
+        ) -> Result<V::Value, Self::Error> {
+            self.0 = fields;
+            Err(de::Error::custom("only looking for the field names"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+            option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+            ignored_any
+        }
+    }
 
-    Ok((toml_parsed, content.to_string()))
+    let mut names = FieldNames(&[]);
+    let _ = T::deserialize(&mut names);
+    names.0
 }
 
-pub fn parse_post_meta(file: &str) -> Result<(NewsMeta, String), anyhow::Error> {
-    let (raw_post, content) = parse_front_matter_and_fetch_contents::<RawNewsMeta>(file)?;
+/// Front matter fields holding dates. YAML has no dates, so they come through as strings and only
+/// these are turned back into the [`toml::value::Date`]s the metadata expects. Any other string,
+/// e.g. `title: "2025-02-02"`, stays a string.
+const DATE_FIELDS: [&str; 3] = ["date", "release_date", "publish_at"];
+
+/// YAML front matter as TOML, so both are read the same way. Dates are left to [`DATE_FIELDS`].
+fn yaml_to_toml(value: serde_yaml::Value) -> toml::Value {
+    use serde_yaml::Value as Yaml;
+
+    match value {
+        Yaml::Null => toml::Value::Table(toml::Table::new()),
+        Yaml::Bool(bool) => toml::Value::Boolean(bool),
+        Yaml::Number(number) => match number.as_i64() {
+            Some(integer) => toml::Value::Integer(integer),
+            None => toml::Value::Float(number.as_f64().unwrap_or_default()),
+        },
+        Yaml::String(string) => toml::Value::String(string),
+        Yaml::Sequence(sequence) => {
+            toml::Value::Array(sequence.into_iter().map(yaml_to_toml).collect())
+        }
+        Yaml::Mapping(mapping) => toml::Value::Table(
+            mapping
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| {
+                    let key = match key {
+                        Yaml::String(key) => key,
+                        key => serde_yaml::to_string(&key)
+                            .unwrap_or_default()
+                            .trim()
+                            .to_string(),
+                    };
+                    (key, yaml_to_toml(value))
+                })
+                .collect(),
+        ),
+        Yaml::Tagged(tagged) => yaml_to_toml(tagged.value),
+    }
+}
+
+pub fn parse_post_meta(file: &str) -> Result<Parsed<NewsMeta>, SourceIssue> {
+    let Parsed {
+        meta: raw_post,
+        content,
+        warnings,
+    } = parse_front_matter_and_fetch_contents::<RawNewsMeta>(file)?;
 
     let new_short = match raw_post.short {
         Some(shrt) => shrt,
         None => format!("{}...", content.chars().take(50).collect::<String>()),
     };
 
-    Ok((
-        NewsMeta {
+    Ok(Parsed {
+        meta: NewsMeta {
             title: raw_post.title,
             author: raw_post.author,
             header_image: raw_post.header_image,
             date: raw_post.date,
             short: new_short,
             sns_links: raw_post.sns_links,
+            status: raw_post.status,
+            publish_at: raw_post.publish_at,
+            slug: raw_post.slug,
+            aliases: raw_post.aliases,
         },
         content,
-    ))
+        warnings,
+    })
 }
 
-pub fn parse_work_meta(file: &str) -> Result<(WorkMeta, String), anyhow::Error> {
-    let (raw_work, content) = parse_front_matter_and_fetch_contents::<RawWorkMeta>(file)?;
+pub fn parse_work_meta(file: &str) -> Result<Parsed<WorkMeta>, SourceIssue> {
+    let Parsed {
+        meta: raw_work,
+        content,
+        warnings,
+    } = parse_front_matter_and_fetch_contents::<RawWorkMeta>(file)?;
 
+    Ok(Parsed {
+        meta: work_meta(raw_work)?,
+        content,
+        warnings,
+    })
+}
+
+/// Checks the front matter of a work and works out how it is displayed.
+pub fn work_meta(raw_work: RawWorkMeta) -> Result<WorkMeta, SourceIssue> {
     let coi = match &raw_work.cover_image {
         Some(coverimg) => CoverOrImage::Cover(coverimg.to_string()),
         None => match &raw_work.link {
@@ -60,42 +557,74 @@ pub fn parse_work_meta(file: &str) -> Result<(WorkMeta, String), anyhow::Error>
             None => match &raw_work.file {
                 Some(f) => CoverOrImage::AudioFile(f.to_string()),
                 None => {
-                    return Err(anyhow::Error::msg(
+                    return Err(SourceIssue::new(
+                        None,
                         "Could not find a suitable display. Please ensure one of the following is set: `link`, `cover`, `file`.",
-                    ))?;
+                    ));
                 }
             },
         },
     };
+    if let Some(file) = &raw_work.file {
+        let extension = Utf8Path::new(file).extension().unwrap_or_default();
+        if !AUDIO_EXTENSIONS.contains(&extension.to_lowercase().as_str()) {
+            return Err(SourceIssue::new(
+                None,
+                format!(
+                    "メタデータフィルド`file`でエーラ発生: `{file}`は{}のどれかにしてください。 - `file` must be one of {}",
+                    AUDIO_EXTENSIONS.join("・"),
+                    AUDIO_EXTENSIONS.join(", ")
+                ),
+            ));
+        }
+    }
 
-    Ok((
-        WorkMeta {
-            title: raw_work.title,
-            author: raw_work.author,
-            collaborators: raw_work.collaborators,
-            date: raw_work.date,
-            short: raw_work.short,
-            display: coi,
-            cover_image: raw_work.cover_image,
-            link: raw_work.link,
-            file: raw_work.file,
-            remix_original_work: raw_work.remix_original_work,
-            featured: raw_work.featured,
-            streaming: raw_work.streaming,
-            duration_seconds: raw_work.duration_seconds,
-        },
-        content,
-    ))
+    Ok(WorkMeta {
+        title: raw_work.title,
+        author: raw_work.author,
+        collaborators: raw_work.collaborators,
+        date: raw_work.date,
+        short: raw_work.short,
+        display: coi,
+        cover_image: raw_work.cover_image,
+        link: raw_work.link,
+        file: raw_work.file,
+        remix_original_work: raw_work.remix_original_work,
+        featured: raw_work.featured,
+        streaming: raw_work.streaming,
+        duration_seconds: raw_work.duration_seconds,
+        status: raw_work.status,
+        publish_at: raw_work.publish_at,
+        slug: raw_work.slug,
+        aliases: raw_work.aliases,
+    })
 }
 
-pub fn robots_txt() -> Result<Page, RuntimeError> {
+pub fn robots_txt(site_root: &Utf8Path) -> Result<Page, RuntimeError> {
+    let path = site_root.join("robots.txt");
     let mut robots = String::new();
-    File::open("robots.txt")
-        .unwrap()
-        .read_to_string(&mut robots)
-        .unwrap();
+    File::open(&path)
+        .and_then(|mut file| file.read_to_string(&mut robots))
+        .map_err(|why| RuntimeError::new(why).context(format!("reading {path}")))?;
     Ok(Page::text(
         camino::Utf8PathBuf::from_str("robots.txt")?,
         robots,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::work::RawWorkMeta;
+
+    #[test]
+    fn yaml_dates_are_only_read_as_dates_where_dates_go() {
+        let file = "---\ntitle: \"2025-02-02\"\nauthor: Reibun\ndate: 2025-02-02\nshort: \"12:00:00\"\npublish_at: \"2025-11-22T10:00:00\"\nlink: https://www.youtube.com/watch?v=dQw4w9WgXcQ\n---\n";
+        let Parsed { meta, .. } = parse_front_matter_and_fetch_contents::<RawWorkMeta>(file)
+            .unwrap_or_else(|issue| panic!("{}", issue.message));
+        assert_eq!(meta.title, "2025-02-02");
+        assert_eq!(meta.short.as_deref(), Some("12:00:00"));
+        assert_eq!(meta.date.to_string(), "2025-02-02");
+        assert!(meta.publish_at.is_some());
+    }
+}
@@ -0,0 +1,229 @@
+use crate::check::Diagnostics;
+use crate::read::SiteContent;
+use crate::routes::{Route, Routes};
+use crate::templates::news::post_reference;
+use crate::templates::works::{album_reference, work_reference};
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
+use hauchiwa::Page;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// An old URL that should send visitors on to where the content lives now.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Redirect {
+    /// The old URL, as written in `aliases` or as the page used to be named.
+    pub from: String,
+    /// Site-absolute link to the page now, from [`Routes::url`].
+    pub to: String,
+    /// The content file the page is built from.
+    pub file: Utf8PathBuf,
+    /// Whether someone asked for this in `aliases`, rather than it being an old hashed page name.
+    pub alias: bool,
+}
+
+impl Redirect {
+    /// Where the redirect page is written, the same way hauchiwa would write a page there.
+    pub fn path(&self) -> Utf8PathBuf {
+        Page::html(self.from.trim_start_matches('/'), "").path
+    }
+}
+
+/// The `aliases` of every piece of content, and the hashed page names used before slugs.
+pub fn redirects(content: &SiteContent, routes: &Routes) -> Vec<Redirect> {
+    let mut redirects = vec![];
+    let mut add = |file: &Utf8Path, to: String, old: Option<String>, aliases: &[String]| {
+        if let Some(old) = old.filter(|old| old != &to) {
+            redirects.push(Redirect {
+                from: old,
+                to: to.clone(),
+                file: file.to_path_buf(),
+                alias: false,
+            });
+        }
+        for alias in aliases {
+            redirects.push(Redirect {
+                from: alias.clone(),
+                to: to.clone(),
+                file: file.to_path_buf(),
+                alias: true,
+            });
+        }
+    };
+
+    for member in &content.members {
+        let meta = &member.data.meta;
+        let to = routes.url(Route::Member(&meta.ascii_name));
+        add(&member.file, to, None, &meta.aliases);
+    }
+    for work in &content.works {
+        let meta = &work.data.meta;
+        add(
+            &work.file,
+            routes.url(Route::Work(meta)),
+            Some(format!(
+                "/works/releases/{}.html",
+                work_reference(&meta.title, &meta.author)
+            )),
+            &meta.aliases,
+        );
+    }
+    for album in &content.albums {
+        let meta = &album.data.meta;
+        add(
+            &album.file,
+            routes.url(Route::Album(meta)),
+            Some(format!(
+                "/works/albums/{}.html",
+                album_reference(&meta.title, &meta.front_cover)
+            )),
+            &meta.aliases,
+        );
+    }
+    for post in &content.news {
+        let meta = &post.data.meta;
+        add(
+            &post.file,
+            routes.url(Route::Post(meta)),
+            Some(format!("/news/{}.html", post_reference(meta))),
+            &meta.aliases,
+        );
+    }
+    redirects
+}
+
+/// Reports aliases that are not paths on the site, or that land on a page or on another alias.
+/// Old hashed page names give way to anything real, so those are dropped instead.
+pub fn check_redirects<'a>(
+    redirects: &mut Vec<Redirect>,
+    pages: impl IntoIterator<Item = &'a Utf8Path>,
+    diagnostics: &mut Diagnostics,
+) {
+    let pages = pages.into_iter().collect::<HashSet<&Utf8Path>>();
+    redirects.retain(|redirect| redirect.alias || !pages.contains(redirect.path().as_path()));
+    // Dropped rather than only reported, their pages would be written outside the site.
+    redirects.retain(|redirect| {
+        let alias = &redirect.from;
+        if !redirect.alias || is_site_path(alias) {
+            return true;
+        }
+        diagnostics.error(
+            &redirect.file,
+            format!(
+                "メタデータフィルド`aliases`でエーラ発生: `{alias}`は`/`から始まるサイト内のパスにしてください。`..`、`?`、`#`と空白は使えません。 - Alias `{alias}` must be a path on this site starting with `/`, without `..`, `?`, `#` or whitespace"
+            ),
+        );
+        false
+    });
+
+    let mut seen = HashMap::<Utf8PathBuf, &Redirect>::new();
+    for redirect in redirects.iter() {
+        if !redirect.alias {
+            seen.entry(redirect.path()).or_insert(redirect);
+            continue;
+        }
+        let alias = &redirect.from;
+        let path = redirect.path();
+        if pages.contains(path.as_path()) {
+            diagnostics.error(
+                &redirect.file,
+                format!(
+                    "メタデータフィルド`aliases`でエーラ発生: `{alias}`はもうあるページ{path}と同じURLです。 - Alias `{alias}` collides with the page {path}"
+                ),
+            );
+            continue;
+        }
+        match seen.get(&path) {
+            Some(other) if other.to != redirect.to => diagnostics.error(
+                &redirect.file,
+                format!(
+                    "メタデータフィルド`aliases`でエーラ発生: `{alias}`は{}の`{}`と同じURLです。 - Alias `{alias}` collides with `{}` of {}",
+                    other.file, other.from, other.from, other.file
+                ),
+            ),
+            Some(_) => {}
+            None => {
+                seen.insert(path, redirect);
+            }
+        }
+    }
+
+    // The same old URL to the same page only needs one redirect.
+    let mut written = HashSet::new();
+    redirects.retain(|redirect| written.insert(redirect.path()));
+}
+
+/// Whether `alias` is a path inside the site that can be written out as a page and as a line of
+/// `_redirects`.
+fn is_site_path(alias: &str) -> bool {
+    let Some(path) = alias.strip_prefix('/') else {
+        return false;
+    };
+    !alias.contains(['?', '#'])
+        && !alias.contains(char::is_whitespace)
+        && Utf8Path::new(path)
+            .components()
+            .all(|component| matches!(component, Utf8Component::Normal(_)))
+}
+
+/// Every redirect in the `_redirects` format read by Netlify and Cloudflare Pages, which can
+/// answer with a real 301 instead of the meta-refresh pages.
+pub fn redirects_file(
+    redirects: &[Redirect],
+    link: impl Fn(&str) -> Result<String, anyhow::Error>,
+) -> Result<String, anyhow::Error> {
+    let mut file = String::new();
+    for redirect in redirects {
+        writeln!(file, "{} {} 301", redirect.from, link(&redirect.to)?)?;
+    }
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alias(from: &str, to: &str, file: &str) -> Redirect {
+        Redirect {
+            from: from.to_string(),
+            to: to.to_string(),
+            file: Utf8PathBuf::from(file),
+            alias: true,
+        }
+    }
+
+    #[test]
+    fn aliases_stay_inside_the_site() {
+        for bad in [
+            "/../../x.html",
+            "/works/../../../etc.html",
+            "//example.com/x.html",
+            "x.html",
+            "/x.html?page=2",
+            "/x.html#top",
+            "/old page.html",
+        ] {
+            let mut redirects = vec![alias(bad, "/works/x.html", "works/x.md")];
+            let mut diagnostics = Diagnostics::default();
+            check_redirects(&mut redirects, [], &mut diagnostics);
+            assert_eq!(diagnostics.error_count(), 1, "{bad}");
+            assert!(redirects.is_empty(), "{bad}");
+        }
+        assert!(is_site_path("/works/old.html"));
+    }
+
+    #[test]
+    fn aliases_may_not_collide() {
+        let mut redirects = vec![
+            alias("/old.html", "/works/a.html", "works/a.md"),
+            alias("/old.html", "/works/b.html", "works/b.md"),
+            alias("/index.html", "/works/a.html", "works/a.md"),
+        ];
+        let mut diagnostics = Diagnostics::default();
+        check_redirects(
+            &mut redirects,
+            [Utf8Path::new("index.html")],
+            &mut diagnostics,
+        );
+        assert_eq!(diagnostics.error_count(), 2);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::album::AlbumMeta;
+use crate::config::SiteConfig;
+use crate::news::NewsMeta;
+use crate::templates::news::post_slug;
+use crate::templates::works::{album_slug, work_slug};
+use crate::work::WorkMeta;
+use camino::Utf8PathBuf;
+
+/// A page of the site, for [`Routes`] to place.
+#[derive(Copy, Clone, Debug)]
+pub enum Route<'a> {
+    Home,
+    NotFound,
+    Join,
+    Members,
+    Works,
+    News,
+    /// A member's profile, by `ascii_name`.
+    Member(&'a str),
+    Work(&'a WorkMeta),
+    Album(&'a AlbumMeta),
+    Post(&'a NewsMeta),
+}
+
+impl Route<'_> {
+    /// Where the page lives, without an extension, e.g. `members/Mayday`.
+    fn name(&self) -> String {
+        match self {
+            Route::Home => "index".to_string(),
+            Route::NotFound => "404".to_string(),
+            Route::Join => "join".to_string(),
+            Route::Members => "members".to_string(),
+            Route::Works => "works".to_string(),
+            Route::News => "news".to_string(),
+            Route::Member(ascii_name) => format!("members/{ascii_name}"),
+            Route::Work(meta) => format!("works/releases/{}", work_slug(meta)),
+            Route::Album(meta) => format!("works/albums/{}", album_slug(meta)),
+            Route::Post(meta) => format!("news/{}", post_slug(meta)),
+        }
+    }
+}
+
+/// Decides where every page is written and what URL links to it. Links are site-absolute;
+/// rewriting puts the configured `site_url`, and any path prefix in it, in front.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Routes {
+    /// Write `members/Mayday/index.html` and link to `/members/Mayday/`, instead of
+    /// `members/Mayday.html`.
+    pub clean_urls: bool,
+}
+
+impl Routes {
+    pub fn new(config: &SiteConfig) -> Routes {
+        Routes {
+            clean_urls: config.clean_urls,
+        }
+    }
+
+    /// The output path of `route`, relative to the output directory.
+    pub fn path(&self, route: Route) -> Utf8PathBuf {
+        match route {
+            // Hosts look for these by name.
+            Route::Home | Route::NotFound => format!("{}.html", route.name()).into(),
+            _ if self.clean_urls => format!("{}/index.html", route.name()).into(),
+            _ => format!("{}.html", route.name()).into(),
+        }
+    }
+
+    /// The site-absolute link to `route`.
+    pub fn url(&self, route: Route) -> String {
+        match route {
+            Route::Home if self.clean_urls => "/".to_string(),
+            Route::NotFound => format!("/{}.html", route.name()),
+            _ if self.clean_urls => format!("/{}/", route.name()),
+            _ => format!("/{}.html", route.name()),
+        }
+    }
+}
@@ -0,0 +1,752 @@
+use crate::FRONT_MATTER_SPLIT;
+use crate::album::{AlbumMeta, AlbumType};
+use crate::check::Diagnostics;
+use crate::member::MemberMeta;
+use crate::news::{NewsMeta, RawNewsMeta};
+use crate::publish::ContentStatus;
+use crate::read::{SiteContent, work_meta};
+use crate::routes::{Route, Routes};
+use crate::templates::news::post_reference;
+use crate::templates::works::{album_reference, work_reference};
+use crate::util::slugify;
+use crate::work::RawWorkMeta;
+use anyhow::{Context as _, anyhow, bail};
+use camino::{Utf8Path, Utf8PathBuf};
+use chrono::Datelike;
+use clap::{ArgGroup, Subcommand};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use toml::Value;
+use toml::value::Date;
+use url::Url;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum NewContent {
+    /// A member profile, `members/{ascii_name}.md`.
+    Member {
+        /// English letters only. Also used to find the icon, so mind the casing.
+        #[arg(long)]
+        ascii_name: String,
+        /// Display name. Defaults to the ascii name.
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// A work. One of `--link`, `--cover-image` or `--file` is required.
+    #[command(group(ArgGroup::new("display").required(true).args(["link", "cover_image", "file"])))]
+    Work {
+        /// Ascii name of the member who made it.
+        #[arg(long)]
+        author: String,
+        #[arg(long)]
+        title: String,
+        /// YouTube, niconico, SoundCloud or Spotify link.
+        #[arg(long)]
+        link: Option<Url>,
+        /// Image in `images/`.
+        #[arg(long)]
+        cover_image: Option<String>,
+        /// Audio file in `audio/`.
+        #[arg(long)]
+        file: Option<String>,
+        /// Name of the page, for titles that cannot be romanized or whose page is taken.
+        #[arg(long)]
+        slug: Option<String>,
+    },
+    /// An album.
+    Album {
+        #[arg(long)]
+        title: String,
+        /// Image in `images/`.
+        #[arg(long)]
+        front_cover: String,
+        /// Ascii name of the member who drew the cover, or the name of whoever did if they are
+        /// not a member.
+        #[arg(long)]
+        front_cover_illustrator: String,
+        /// Name of the page, for titles that cannot be romanized or whose page is taken.
+        #[arg(long)]
+        slug: Option<String>,
+    },
+    /// A news post.
+    Post {
+        #[arg(long)]
+        title: String,
+        /// Ascii name of the member writing it. Left out for posts by the club itself.
+        #[arg(long)]
+        author: Option<String>,
+        /// Name of the page, for titles that cannot be romanized or whose page is taken.
+        #[arg(long)]
+        slug: Option<String>,
+    },
+}
+
+/// A front matter key, explained in the generated file.
+struct Field {
+    key: &'static str,
+    comment: &'static str,
+    /// Written commented out when the field is not set.
+    example: &'static str,
+}
+
+const MEMBER_FIELDS: &[Field] = &[
+    Field {
+        key: "name",
+        comment: "活動名",
+        example: "name = \"例文\"",
+    },
+    Field {
+        key: "ascii_name",
+        comment: "英語字で活動名 - アイコンは\"images/icon/{ascii_name}.jpg\"に入れてください。ケース・センシティブ!",
+        example: "ascii_name = \"Reibun\"",
+    },
+    Field {
+        key: "short",
+        comment: "狭い自分紹介",
+        example: "short = \"\"",
+    },
+    Field {
+        key: "department",
+        comment: "任意: 学部",
+        example: "department = \"\"",
+    },
+    Field {
+        key: "position",
+        comment: "任意: 役職",
+        example: "position = \"\"",
+    },
+    Field {
+        key: "entry_year",
+        comment: "任意: 入年",
+        example: "entry_year = 2025",
+    },
+    Field {
+        key: "status",
+        comment: "\"published\", \"unlisted\" (リンクを知る人だけ) OR \"draft\" (下書き)",
+        example: "status = \"draft\"",
+    },
+    Field {
+        key: "publish_at",
+        comment: "任意: 公開日時 (JST) - この時まではビルドされません",
+        example: "publish_at = 2025-11-22T10:00:00",
+    },
+    Field {
+        key: "aliases",
+        comment: "任意: 古いURL - 新しいページに転送されます",
+        example: "aliases = [ \"/works/releases/old.html\" ]",
+    },
+    Field {
+        key: "links",
+        comment: "SNS リンク",
+        example: "links = [ \"https://twitter.com/toudaivocadou\" ]",
+    },
+];
+
+const WORK_FIELDS: &[Field] = &[
+    Field {
+        key: "title",
+        comment: "作品の題目",
+        example: "title = \"\"",
+    },
+    Field {
+        key: "author",
+        comment: "作家 (英語字)",
+        example: "author = \"\"",
+    },
+    Field {
+        key: "date",
+        comment: "作った年月日 (ISO-8601)",
+        example: "date = 2025-02-02",
+    },
+    Field {
+        key: "short",
+        comment: "狭い説明",
+        example: "short = \"面白い作品\"",
+    },
+    Field {
+        key: "link",
+        comment: "作品のリンク (youtube OR nicovideo OR soundcloud OR spotify)",
+        example: "link = \"https://www.youtube.com/watch?v=dQw4w9WgXcQ\"",
+    },
+    Field {
+        key: "cover_image",
+        comment: "カバーイメージがあるの場合 (写真ファイルは\"images\"に入れてください)",
+        example: "cover_image = \"circle-photo.jpg\"",
+    },
+    Field {
+        key: "file",
+        comment: "音声ファイルがあるの場合 (mp3・ogg・opus・flac・wav、\"audio\"に入れてください)",
+        example: "file = \"work.ogg\"",
+    },
+    Field {
+        key: "collaborators",
+        comment: "この作品がコラボ作品の場合、メンバーの英語ネームが入れられます",
+        example: "collaborators = [ \"Mitsumori\" ]",
+    },
+    Field {
+        key: "featured",
+        comment: "この作品をメンバーページにフィーチャー希望の場合、`true`で設定してください",
+        example: "featured = true",
+    },
+    Field {
+        key: "remix_original_work",
+        comment: "リミックスの場合、こちらにオリジナル曲のリンクを入れてください",
+        example: "remix_original_work = \"\"",
+    },
+    Field {
+        key: "streaming",
+        comment: "配信リンク",
+        example: "streaming = [ \"https://open.spotify.com/track/...\" ]",
+    },
+    Field {
+        key: "status",
+        comment: "\"published\", \"unlisted\" (リンクを知る人だけ) OR \"draft\" (下書き)",
+        example: "status = \"draft\"",
+    },
+    Field {
+        key: "publish_at",
+        comment: "任意: 公開日時 (JST) - この時まではビルドされません",
+        example: "publish_at = 2025-11-22T10:00:00",
+    },
+    Field {
+        key: "slug",
+        comment: "任意: URLの名前 (英数字と-) - なければ題目から作ります",
+        example: "slug = \"hello-world\"",
+    },
+    Field {
+        key: "aliases",
+        comment: "任意: 古いURL - 新しいページに転送されます",
+        example: "aliases = [ \"/works/releases/old.html\" ]",
+    },
+    Field {
+        key: "duration_seconds",
+        comment: "長さ (秒) - `file`があればファイルから読み込まれます",
+        example: "duration_seconds = 180",
+    },
+];
+
+const ALBUM_FIELDS: &[Field] = &[
+    Field {
+        key: "title",
+        comment: "アルバムの題目",
+        example: "title = \"\"",
+    },
+    Field {
+        key: "subtitle",
+        comment: "任意: 副題",
+        example: "subtitle = \"\"",
+    },
+    Field {
+        key: "release_date",
+        comment: "発売年月日 (ISO-8601)",
+        example: "release_date = 2025-10-28",
+    },
+    Field {
+        key: "short",
+        comment: "狭い説明",
+        example: "short = \"\"",
+    },
+    Field {
+        key: "album_type",
+        comment: "\"Solo\", \"GroupExternal\" OR \"ToudaiVocadou\"",
+        example: "album_type = \"ToudaiVocadou\"",
+    },
+    Field {
+        key: "contributors",
+        comment: "参加したメンバーの英語ネーム",
+        example: "contributors = [ \"Mitsumori\" ]",
+    },
+    Field {
+        key: "extra_contributors",
+        comment: "メンバーじゃない参加者",
+        example: "extra_contributors = [ \"\" ]",
+    },
+    Field {
+        key: "front_cover",
+        comment: "フロントカーバー (\"images\"に入れてください)",
+        example: "front_cover = \"\"",
+    },
+    Field {
+        key: "front_cover_illustrator",
+        comment: "イラストレーター",
+        example: "front_cover_illustrator = \"\"",
+    },
+    Field {
+        key: "front_cover_illustrator_not_on_site",
+        comment: "イラストレーターがメンバーじゃない場合`true`",
+        example: "front_cover_illustrator_not_on_site = true",
+    },
+    Field {
+        key: "crossfade_demonstration",
+        comment: "任意: クロスフェードのリンク",
+        example: "crossfade_demonstration = \"\"",
+    },
+    Field {
+        key: "playlist_link",
+        comment: "任意: プレイリストのリンク",
+        example: "playlist_link = \"\"",
+    },
+    Field {
+        key: "sns_links",
+        comment: "SNS ポスト",
+        example: "sns_links = [ \"https://twitter.com/toudaivocadou/status/...\" ]",
+    },
+    Field {
+        key: "status",
+        comment: "\"published\", \"unlisted\" (リンクを知る人だけ) OR \"draft\" (下書き)",
+        example: "status = \"draft\"",
+    },
+    Field {
+        key: "publish_at",
+        comment: "任意: 公開日時 (JST) - この時まではビルドされません",
+        example: "publish_at = 2025-11-22T10:00:00",
+    },
+    Field {
+        key: "slug",
+        comment: "任意: URLの名前 (英数字と-) - なければ題目から作ります",
+        example: "slug = \"hello-world\"",
+    },
+    Field {
+        key: "aliases",
+        comment: "任意: 古いURL - 新しいページに転送されます",
+        example: "aliases = [ \"/works/releases/old.html\" ]",
+    },
+    Field {
+        key: "other_covers",
+        comment: "他のイラスト",
+        example: "[other_covers.Inside]\nlink = \"\"\nillustrator = \"\"\nillustrator_is_not_on_site = false",
+    },
+    Field {
+        key: "tracklist",
+        comment: "トラックリスト - 曲ごとに[[tracklist]]を繰り返してください。`file`はaudio/の中の音声ファイルで、長さが読み込まれます",
+        example: "[[tracklist]]\nauthor = \"\"\ntitle = \"\"\nduration_seconds = 180\n# file = \"track.flac\"\non_site = false\nexternal_author = false",
+    },
+];
+
+const POST_FIELDS: &[Field] = &[
+    Field {
+        key: "title",
+        comment: "題目",
+        example: "title = \"\"",
+    },
+    Field {
+        key: "author",
+        comment: "作家 (英語字)",
+        example: "author = \"\"",
+    },
+    Field {
+        key: "date",
+        comment: "年月日 (ISO-8601)",
+        example: "date = 2025-05-09",
+    },
+    Field {
+        key: "short",
+        comment: "任意: 狭い説明 - なければ本文の頭から作ります",
+        example: "short = \"\"",
+    },
+    Field {
+        key: "header_image",
+        comment: "任意: 写真",
+        example: "header_image = \"\"",
+    },
+    Field {
+        key: "status",
+        comment: "\"published\", \"unlisted\" (リンクを知る人だけ) OR \"draft\" (下書き)",
+        example: "status = \"draft\"",
+    },
+    Field {
+        key: "publish_at",
+        comment: "任意: 公開日時 (JST) - この時まではビルドされません",
+        example: "publish_at = 2025-11-22T10:00:00",
+    },
+    Field {
+        key: "slug",
+        comment: "任意: URLの名前 (英数字と-) - なければ題目から作ります",
+        example: "slug = \"hello-world\"",
+    },
+    Field {
+        key: "aliases",
+        comment: "任意: 古いURL - 新しいページに転送されます",
+        example: "aliases = [ \"/works/releases/old.html\" ]",
+    },
+    Field {
+        key: "sns_links",
+        comment: "SNS ポスト",
+        example: "sns_links = [ \"https://twitter.com/toudaivocadou/status/...\" ]",
+    },
+];
+
+const BODY: &str = "こちらに内容を入力
+
+**ディスコードみたいマークダウンフォーマットできます**
+
+ツイータなどsnsのポストを入れたければ
+{{ sns_embed(\"https://x.com/あなたのユーザーネーム/ポストID\") }}
+を使ってください
+
+マークダウン使用ガイド: https://qiita.com/masterpiecehack/items/990683b706b70b88b4c7
+";
+
+/// Writes a new content file into the data root and returns its path.
+pub fn new_content(
+    data_root: &Utf8Path,
+    routes: &Routes,
+    kind: NewContent,
+) -> Result<Utf8PathBuf, anyhow::Error> {
+    // Broken files are `check`'s business, the existing content is only needed for collisions.
+    let content = SiteContent::from_disk(data_root, &mut Diagnostics::default())?;
+    let name_map = content.name_map();
+    let date = today();
+
+    let (path, front_matter) = match kind {
+        NewContent::Member { ascii_name, name } => {
+            if ascii_name.is_empty()
+                || !ascii_name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                bail!(
+                    "ascii_nameは英数字のみ使ってください - ascii_name may only contain ascii letters, digits, `_` and `-`: {ascii_name}"
+                );
+            }
+            if let Some(existing) = name_map
+                .keys()
+                .find(|existing| existing.eq_ignore_ascii_case(&ascii_name))
+            {
+                bail!("メンバー {existing} はもういます - Member {existing} already exists");
+            }
+
+            let meta = MemberMeta {
+                name: name.unwrap_or_else(|| ascii_name.clone()),
+                ascii_name: ascii_name.clone(),
+                department: None,
+                position: None,
+                entry_year: Some(date.year.into()),
+                short: String::new(),
+                links: HashSet::new(),
+                status: ContentStatus::Published,
+                publish_at: None,
+                aliases: vec![],
+            };
+            (
+                data_root.join(format!("members/{ascii_name}.md")),
+                front_matter(&meta, MEMBER_FIELDS)?,
+            )
+        }
+        NewContent::Work {
+            author,
+            title,
+            link,
+            cover_image,
+            file,
+            slug,
+        } => {
+            ensure_member(&name_map, &author)?;
+            let reference = work_reference(&title, &author);
+
+            let meta = RawWorkMeta {
+                title: title.clone(),
+                author,
+                collaborators: vec![],
+                date,
+                short: None,
+                cover_image,
+                link,
+                file,
+                remix_original_work: None,
+                featured: false,
+                streaming: vec![],
+                duration_seconds: None,
+                status: ContentStatus::Published,
+                publish_at: None,
+                slug,
+                aliases: vec![],
+            };
+            let page = work_meta(meta.clone()).map_err(|issue| anyhow!(issue.message))?;
+            ensure_page_free(
+                routes.path(Route::Work(&page)),
+                &title,
+                content
+                    .works
+                    .iter()
+                    .map(|work| (routes.path(Route::Work(&work.data.meta)), &work.file)),
+            )?;
+            (
+                data_root.join(format!("works/{}.md", file_stem(&title, &reference))),
+                front_matter(&meta, WORK_FIELDS)?,
+            )
+        }
+        NewContent::Album {
+            title,
+            front_cover,
+            front_cover_illustrator,
+            slug,
+        } => {
+            let reference = album_reference(&title, &front_cover);
+
+            let meta = AlbumMeta {
+                title: title.clone(),
+                subtitle: None,
+                release_date: date,
+                short: String::new(),
+                album_type: AlbumType::ToudaiVocadou,
+                contributors: vec![],
+                extra_contributors: vec![],
+                crossfade_demonstration: None,
+                front_cover,
+                front_cover_illustrator_not_on_site: !name_map
+                    .contains_key(&front_cover_illustrator),
+                front_cover_illustrator,
+                other_covers: HashMap::new(),
+                playlist_link: None,
+                tracklist: vec![],
+                sns_links: vec![],
+                status: ContentStatus::Published,
+                publish_at: None,
+                slug,
+                aliases: vec![],
+            };
+            ensure_page_free(
+                routes.path(Route::Album(&meta)),
+                &title,
+                content
+                    .albums
+                    .iter()
+                    .map(|album| (routes.path(Route::Album(&album.data.meta)), &album.file)),
+            )?;
+            (
+                data_root.join(format!("albums/{}.md", file_stem(&title, &reference))),
+                front_matter(&meta, ALBUM_FIELDS)?,
+            )
+        }
+        NewContent::Post {
+            title,
+            author,
+            slug,
+        } => {
+            if let Some(author) = &author {
+                ensure_member(&name_map, author)?;
+            }
+            let page = NewsMeta {
+                title: title.clone(),
+                author: author.clone(),
+                header_image: None,
+                date,
+                short: String::new(),
+                sns_links: vec![],
+                status: ContentStatus::Published,
+                publish_at: None,
+                slug: slug.clone(),
+                aliases: vec![],
+            };
+            let reference = post_reference(&page);
+            ensure_page_free(
+                routes.path(Route::Post(&page)),
+                &title,
+                content
+                    .news
+                    .iter()
+                    .map(|post| (routes.path(Route::Post(&post.data.meta)), &post.file)),
+            )?;
+
+            let meta = RawNewsMeta {
+                title: title.clone(),
+                author,
+                header_image: None,
+                date,
+                short: None,
+                sns_links: vec![],
+                status: ContentStatus::Published,
+                publish_at: None,
+                slug,
+                aliases: vec![],
+            };
+            (
+                data_root.join(format!("posts/{}.md", file_stem(&title, &reference))),
+                front_matter(&meta, POST_FIELDS)?,
+            )
+        }
+    };
+
+    if path.exists() {
+        bail!("ファイル {path} はもうあります - {path} already exists");
+    }
+    std::fs::write(
+        &path,
+        format!("{front_matter}{FRONT_MATTER_SPLIT}\n\n{BODY}"),
+    )
+    .with_context(|| format!("writing {path}"))?;
+    Ok(path)
+}
+
+/// Fails if the page of new content would be written at the same `path` as the page of one of
+/// the `existing` content files.
+fn ensure_page_free<'a>(
+    path: Utf8PathBuf,
+    title: &str,
+    mut existing: impl Iterator<Item = (Utf8PathBuf, &'a Utf8PathBuf)>,
+) -> Result<(), anyhow::Error> {
+    match existing.find(|(existing, _)| existing == &path) {
+        Some((_, file)) => bail!(
+            "「{title}」のページ{path}は{file}のページと重なります。--slugで別の名前を付けてください。 - The page {path} for {title} is already taken by {file}, give it another name with --slug"
+        ),
+        None => Ok(()),
+    }
+}
+
+fn ensure_member(
+    name_map: &HashMap<String, String>,
+    ascii_name: &str,
+) -> Result<(), anyhow::Error> {
+    if name_map.contains_key(ascii_name) {
+        return Ok(());
+    }
+    match name_map
+        .keys()
+        .find(|existing| existing.eq_ignore_ascii_case(ascii_name))
+    {
+        Some(existing) => bail!(
+            "{ascii_name} はメンバー中見つかりませんでした。{existing} のことですか？ - {ascii_name} is not a member, did you mean {existing}?"
+        ),
+        None => bail!(
+            "{ascii_name} はメンバー中見つかりませんでした。 英語ネーム使うかどうか確認してください。 - {ascii_name} is not a member"
+        ),
+    }
+}
+
+/// The romanized title, or the page reference if nothing is left of it.
+fn file_stem(title: &str, reference: &str) -> String {
+    slugify(title).unwrap_or_else(|| reference.to_string())
+}
+
+fn today() -> Date {
+    let today = chrono::Local::now().date_naive();
+    Date {
+        year: today.year() as u16,
+        month: today.month() as u8,
+        day: today.day() as u8,
+    }
+}
+
+/// Serializes `meta` and writes every key in `fields` with its explanation. Keys left unset are
+/// written commented out, with an example.
+fn front_matter<T: Serialize>(meta: &T, fields: &[Field]) -> Result<String, anyhow::Error> {
+    // Going through a string turns dates into proper TOML dates rather than serde's stand-in.
+    let mut table = toml::from_str::<toml::Table>(&toml::to_string(meta)?)?;
+    let mut documented = String::new();
+    for field in fields {
+        match table.remove(field.key) {
+            Some(value) if !is_empty_table(field, &value) => {
+                writeln!(documented, "{} = {value} # {}", field.key, field.comment)?;
+            }
+            _ => {
+                writeln!(documented, "# {}", field.comment)?;
+                for line in field.example.lines() {
+                    writeln!(documented, "#{line}")?;
+                }
+            }
+        }
+    }
+
+    // Anything not in `fields` still has to end up in the file, and before any commented out
+    // `[table]` examples.
+    let mut out = String::new();
+    for (key, value) in table {
+        writeln!(out, "{key} = {value}")?;
+    }
+    out.push_str(&documented);
+    Ok(out)
+}
+
+/// Empty tables are left to the `[table]` example, as writing `key = {}` would stop the member
+/// from adding one below.
+fn is_empty_table(field: &Field, value: &Value) -> bool {
+    let empty = match value {
+        Value::Array(array) => array.is_empty(),
+        Value::Table(table) => table.is_empty(),
+        _ => false,
+    };
+    empty && field.example.starts_with('[')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SiteConfig;
+    use crate::read::{Parsed, field_names, parse_front_matter_and_fetch_contents};
+    use serde::de::DeserializeOwned;
+
+    fn sorted<'a>(keys: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+        let mut keys = keys.into_iter().collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys
+    }
+
+    #[test]
+    fn fields_are_those_of_the_metadata() {
+        let keys = |fields: &[Field]| sorted(fields.iter().map(|field| field.key));
+        let names = |names: &'static [&'static str]| sorted(names.iter().copied());
+        assert_eq!(keys(MEMBER_FIELDS), names(field_names::<MemberMeta>()));
+        assert_eq!(keys(WORK_FIELDS), names(field_names::<RawWorkMeta>()));
+        assert_eq!(keys(ALBUM_FIELDS), names(field_names::<AlbumMeta>()));
+        assert_eq!(keys(POST_FIELDS), names(field_names::<RawNewsMeta>()));
+    }
+
+    fn read_back<T: DeserializeOwned>(path: &Utf8Path) -> T {
+        let text = std::fs::read_to_string(path).unwrap();
+        let Parsed { meta, warnings, .. } = parse_front_matter_and_fetch_contents::<T>(&text)
+            .unwrap_or_else(|issue| panic!("{path}: {}", issue.message));
+        assert!(warnings.is_empty(), "{path}: {}", warnings[0].message);
+        meta
+    }
+
+    #[test]
+    fn new_files_read_back() {
+        let data_root = Utf8PathBuf::try_from(std::env::temp_dir())
+            .unwrap()
+            .join(format!("scaffold-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&data_root);
+        for dir in ["members", "works", "albums", "posts"] {
+            std::fs::create_dir_all(data_root.join(dir)).unwrap();
+        }
+        let routes = Routes::new(&SiteConfig::default());
+        let new = |kind| new_content(&data_root, &routes, kind).unwrap();
+
+        let member = new(NewContent::Member {
+            ascii_name: "Reibun".to_string(),
+            name: Some("例文".to_string()),
+        });
+        assert_eq!(read_back::<MemberMeta>(&member).name, "例文");
+
+        let work = new(NewContent::Work {
+            author: "Reibun".to_string(),
+            title: "ハローワールド".to_string(),
+            link: Some(Url::parse("https://www.youtube.com/watch?v=dQw4w9WgXcQ").unwrap()),
+            cover_image: None,
+            file: None,
+            slug: None,
+        });
+        assert_eq!(work.file_name(), Some("harowarudo.md"));
+        assert_eq!(read_back::<RawWorkMeta>(&work).author, "Reibun");
+
+        let album = new(NewContent::Album {
+            title: "Hello, World!".to_string(),
+            front_cover: "front.png".to_string(),
+            front_cover_illustrator: "a月".to_string(),
+            slug: None,
+        });
+        assert!(read_back::<AlbumMeta>(&album).front_cover_illustrator_not_on_site);
+
+        let post = new(NewContent::Post {
+            title: "新歓".to_string(),
+            author: Some("Reibun".to_string()),
+            slug: Some("welcome".to_string()),
+        });
+        assert_eq!(
+            read_back::<RawNewsMeta>(&post).slug.as_deref(),
+            Some("welcome")
+        );
+
+        std::fs::remove_dir_all(&data_root).unwrap();
+    }
+}
@@ -0,0 +1,90 @@
+use crate::SiteData;
+use hauchiwa::{Context, Mode, Page, RuntimeError};
+use log::error;
+use maud::{Markup, PreEscaped, html};
+
+/// Every task writes its last error (or nothing) here while serving, so the browser can show it.
+const ERROR_DIR: &str = "_serve/errors";
+
+/// Names of the tasks that report into [`ERROR_DIR`]. Must match what is passed to
+/// [`surface_errors`].
+const TASKS: &[&str] = &["static", "dynamic"];
+
+const OVERLAY_SCRIPT: &str = r#"
+Promise.all(__TASKS__.map(task => fetch(`/__ERROR_DIR__/${task}.txt`, { cache: "no-store" }).then(res => res.ok ? res.text() : "")))
+    .then(errors => errors.filter(text => text.trim().length > 0))
+    .then(errors => {
+        if (errors.length === 0) {
+            return;
+        }
+        const overlay = document.createElement("div");
+        overlay.style = "position: fixed; inset: 0; z-index: 99999; overflow: auto; padding: 2em; background: rgba(20, 20, 20, 0.95); color: #ff8080; font-family: monospace;";
+        const header = document.createElement("h2");
+        header.textContent = "ビルドエラー - Build failed";
+        overlay.appendChild(header);
+        for (const text of errors) {
+            const pre = document.createElement("pre");
+            pre.style = "white-space: pre-wrap;";
+            pre.textContent = text;
+            overlay.appendChild(pre);
+        }
+        document.body.appendChild(overlay);
+    });
+"#;
+
+/// Live reload and build error overlay scripts, only emitted while serving.
+pub fn serve_scripts(sack: &Context<SiteData>) -> Markup {
+    let Some(refresh) = sack.get_refresh_script() else {
+        return html! {};
+    };
+    let tasks = TASKS
+        .iter()
+        .map(|task| format!("\"{task}\""))
+        .collect::<Vec<String>>()
+        .join(", ");
+    let overlay = OVERLAY_SCRIPT
+        .replace("__TASKS__", &format!("[{tasks}]"))
+        .replace("__ERROR_DIR__", ERROR_DIR);
+
+    html! {
+        script { (PreEscaped(refresh)) }
+        script { (PreEscaped(overlay)) }
+    }
+}
+
+/// While serving, turns a task error into a page the overlay script picks up instead of failing
+/// the build, which would otherwise stop `serve` on startup. Outside of `serve` this does nothing.
+pub fn surface_errors(
+    ctx: &Context<SiteData>,
+    task: &'static str,
+    result: Result<Vec<Page>, RuntimeError>,
+) -> Result<Vec<Page>, RuntimeError> {
+    if !matches!(ctx.get_globals().mode, Mode::Watch) {
+        return result;
+    }
+
+    let error_page = |text: String| Page::text(format!("{ERROR_DIR}/{task}.txt"), text);
+    match result {
+        Ok(mut pages) => {
+            pages.push(error_page(String::new()));
+            Ok(pages)
+        }
+        Err(why) => {
+            let message = format!("{why:?}");
+            error!(
+                "BUILD-{}: {} task failed: {}",
+                ctx.get_globals().data.build_id,
+                task,
+                message
+            );
+            Ok(vec![error_page(message)])
+        }
+    }
+}
+
+/// Whether a task failed while serving and [`surface_errors`] left its pages out of `pages`.
+pub fn has_surfaced_errors(pages: &[&Page]) -> bool {
+    pages
+        .iter()
+        .any(|page| page.path.starts_with(ERROR_DIR) && !page.text.trim().is_empty())
+}
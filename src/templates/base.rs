@@ -1,5 +1,6 @@
 use crate::SiteData;
 use crate::metadata::Metadata;
+use crate::publish::ContentStatus;
 use crate::templates::partials::footer::footer;
 use crate::templates::partials::head::html_head;
 use crate::templates::partials::navbar::navbar;
@@ -26,7 +27,10 @@ where
         html lang="ja" {
             (html_head(sack, metadata, scripts)?)
             body {
-                (navbar(metadata.section))
+                (navbar(&sack.get_globals().data.routes, metadata.section))
+                @if metadata.status == ContentStatus::Draft {
+                    .draft-banner { "下書き - Draft" }
+                }
                 .main-content-container {
                     (inner)
                 }
@@ -1,10 +1,12 @@
+use crate::routes::Route;
 use crate::templates::base::base;
 use crate::templates::partials::navbar::Sections;
-use crate::{SiteData, metadata::Metadata};
+use crate::{SiteData, metadata::Metadata, publish::ContentStatus};
 use hauchiwa::{Context, RuntimeError};
 use maud::{Markup, html};
 
 pub fn notfound(sack: &Context<SiteData>) -> Result<Markup, RuntimeError> {
+    let routes = &sack.get_globals().data.routes;
     let inner = html! {
         section #hero {
             h2 { "このページは見つかりませんでした。" }
@@ -12,7 +14,7 @@ pub fn notfound(sack: &Context<SiteData>) -> Result<Markup, RuntimeError> {
 
         section #content {
             .container {
-                a href="/index.html" .back-button {
+                a href=(routes.url(Route::Home)) .back-button {
                     "メインページに一覧に戻る"
                 }
             }
@@ -22,11 +24,12 @@ pub fn notfound(sack: &Context<SiteData>) -> Result<Markup, RuntimeError> {
     let meta = Metadata {
         page_title: "404 - このページを見つかりませんでした。".to_string(),
         page_image: None,
-        canonical_link: "/404.html".to_string(),
+        canonical_link: routes.url(Route::NotFound),
         section: Sections::Home,
         description: None,
         author: None,
         date: None,
+        status: ContentStatus::Published,
     };
 
     base(sack, &meta, Some(&[]), inner)
@@ -0,0 +1,52 @@
+use super::{EmbedProvider, link_card, link_type, oembed};
+use crate::SiteData;
+use crate::die_linky::SocialLinkType;
+use anyhow::Error;
+use maud::{Markup, PreEscaped, html};
+
+/// Bluesky posts, through the markup their oEmbed endpoint hands out.
+pub struct Bluesky;
+
+impl EmbedProvider for Bluesky {
+    fn name(&self) -> &'static str {
+        "Bluesky"
+    }
+
+    fn handles(&self, link: &str) -> bool {
+        link_type(link) == Some(SocialLinkType::Bluesky)
+    }
+
+    fn render(&self, data: &SiteData, link: &str) -> Result<Markup, Error> {
+        let Some(embed_html) = oembed(data, "https://embed.bsky.app/oembed", link)? else {
+            return Ok(link_card(link));
+        };
+
+        if let Some(html) = embed_html.html {
+            return Ok(PreEscaped(html));
+        } else if let Some(image) = embed_html.url {
+            return Ok(html! {
+                a href=(link) {
+                    img src=(image) alt=(link);
+                }
+            });
+        }
+
+        Err(Error::msg("returned oembed did not match any known items."))
+    }
+
+    fn thumbnails(&self, _data: &SiteData, _link: &str) -> Result<Vec<String>, Error> {
+        Ok(vec![])
+    }
+
+    fn origins(&self) -> &'static [&'static str] {
+        &["https://embed.bsky.app"]
+    }
+
+    fn title(&self, data: &SiteData, link: &str) -> Option<String> {
+        // Already fetched by `render`, so this comes from the cache.
+        let author = oembed(data, "https://embed.bsky.app/oembed", link)
+            .ok()??
+            .author_name?;
+        Some(format!("{author}の投稿 - Post by {author}"))
+    }
+}
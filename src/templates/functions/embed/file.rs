@@ -0,0 +1,90 @@
+use super::EmbedProvider;
+use crate::SiteData;
+use crate::util::AUDIO_EXTENSIONS;
+use anyhow::Error;
+use maud::{Markup, html};
+use url::Url;
+
+/// Linked image files, shown as they are.
+pub struct Image;
+
+impl EmbedProvider for Image {
+    fn name(&self) -> &'static str {
+        "画像 (image)"
+    }
+
+    fn handles(&self, link: &str) -> bool {
+        has_extension(link, &["png", "jpeg", "jpg", "gif"])
+    }
+
+    fn render(&self, _data: &SiteData, link: &str) -> Result<Markup, Error> {
+        Ok(html! {
+            img src=(link);
+        })
+    }
+
+    fn thumbnails(&self, _data: &SiteData, link: &str) -> Result<Vec<String>, Error> {
+        // Pictures in the data root are already on the site.
+        Ok(Url::parse(link)
+            .is_ok_and(|url| url.scheme().starts_with("http"))
+            .then(|| link.to_string())
+            .into_iter()
+            .collect())
+    }
+
+    fn origins(&self) -> &'static [&'static str] {
+        &[]
+    }
+}
+
+/// Linked audio files, with a player and a download link.
+pub struct Audio;
+
+impl EmbedProvider for Audio {
+    fn name(&self) -> &'static str {
+        "音声 (audio)"
+    }
+
+    fn handles(&self, link: &str) -> bool {
+        has_extension(link, &AUDIO_EXTENSIONS)
+    }
+
+    fn render(&self, _data: &SiteData, link: &str) -> Result<Markup, Error> {
+        let name = without_query(link).rsplit('/').next().unwrap_or(link);
+        Ok(audio_player(link, name))
+    }
+
+    fn thumbnails(&self, _data: &SiteData, _link: &str) -> Result<Vec<String>, Error> {
+        Ok(vec![])
+    }
+
+    fn origins(&self) -> &'static [&'static str] {
+        &[]
+    }
+}
+
+/// A player for the audio file at `link`, labelled `title`, with a link to download it for
+/// browsers and visitors that cannot play it on the page.
+pub fn audio_player(link: &str, title: &str) -> Markup {
+    html! {
+        figure .audio-player {
+            figcaption { (title) }
+            audio controls preload="metadata" src=(link) aria-label=(title) {
+                a href=(link) download { "ファイルをダウンロードする - Download the file" }
+            }
+            a .audio-download href=(link) download {
+                "ファイルをダウンロードする - Download the file"
+            }
+        }
+    }
+}
+
+fn has_extension(link: &str, extensions: &[&str]) -> bool {
+    without_query(link)
+        .rsplit_once('.')
+        .is_some_and(|(_, extension)| extensions.contains(&extension.to_lowercase().as_str()))
+}
+
+fn without_query(link: &str) -> &str {
+    link.split(['#', '?']).next().unwrap_or_default()
+}
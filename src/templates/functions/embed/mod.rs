@@ -0,0 +1,229 @@
+mod bluesky;
+mod file;
+mod niconico;
+mod soundcloud;
+mod spotify;
+mod twitter;
+mod youtube;
+
+pub use file::audio_player;
+
+use crate::SiteData;
+use crate::die_linky::SocialLinkType;
+use anyhow::Error;
+use maud::{Markup, Render, html};
+use minijinja::{Error as JinjaError, ErrorKind};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use urlencoding::encode;
+
+/// One platform, or kind of file, that links can be embedded from. Add a provider by
+/// implementing this and listing it in [`PROVIDERS`].
+pub trait EmbedProvider: Sync {
+    /// Shown in errors, e.g. `YouTube`.
+    fn name(&self) -> &'static str;
+
+    /// Whether this provider knows how to embed `link`.
+    fn handles(&self, link: &str) -> bool;
+
+    /// The markup shown in place of `link`.
+    fn render(&self, data: &SiteData, link: &str) -> Result<Markup, Error>;
+
+    /// Where pictures of `link` can be downloaded for cards, best first. Empty if the platform
+    /// does not give any out.
+    fn thumbnails(&self, data: &SiteData, link: &str) -> Result<Vec<String>, Error>;
+
+    /// The third-party origins the embed loads anything from, e.g. `https://www.youtube.com`.
+    /// Embeds with any are put behind a click-to-load facade, unless `embed_facades` is off.
+    fn origins(&self) -> &'static [&'static str];
+
+    /// What `link` is called, for its facade.
+    fn title(&self, _data: &SiteData, _link: &str) -> Option<String> {
+        None
+    }
+
+    /// The class of the box the embed, or its facade, is shown in.
+    fn container(&self) -> &'static str {
+        "embed-container"
+    }
+}
+
+/// Every provider, asked in order. Files go first, so an image hosted on a platform's domain is
+/// still shown as an image.
+pub static PROVIDERS: &[&dyn EmbedProvider] = &[
+    &file::Image,
+    &file::Audio,
+    &twitter::Twitter,
+    &bluesky::Bluesky,
+    &youtube::Youtube,
+    &niconico::Niconico,
+    &soundcloud::Soundcloud,
+    &spotify::Spotify,
+];
+
+/// The provider for `link`, if there is one.
+pub fn provider(link: &str) -> Option<&'static dyn EmbedProvider> {
+    PROVIDERS
+        .iter()
+        .copied()
+        .find(|provider| provider.handles(link))
+}
+
+/// The embed for `link`. `thumbnail` is its self-hosted thumbnail and `title` what it is called
+/// on the site, both shown on the facade. Without a title from the site the platform's is asked
+/// for, and without either the facade shows the provider's name.
+pub fn embed(
+    data: &SiteData,
+    link: &str,
+    thumbnail: Option<&str>,
+    title: Option<&str>,
+) -> Result<Markup, Error> {
+    let provider = provider(link).ok_or_else(|| {
+        Error::msg(format!(
+            "{link}は埋め込めません。 - Don't know how to embed {link}"
+        ))
+    })?;
+    let failed = |why: Error| {
+        why.context(format!(
+            "{}の埋め込みが作れませんでした: {link} - Failed to embed {link} from {}",
+            provider.name(),
+            provider.name()
+        ))
+    };
+    let markup = provider.render(data, link).map_err(failed)?;
+    if !data.config.embed_facades || provider.origins().is_empty() {
+        return Ok(html! {
+            div class=(provider.container()) { (markup) }
+        });
+    }
+
+    let title = title
+        .map(str::to_string)
+        .or_else(|| provider.title(data, link))
+        .unwrap_or_else(|| provider.name().to_string());
+    let hosts = provider
+        .origins()
+        .iter()
+        .map(|origin| origin.trim_start_matches("https://"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Ok(html! {
+        div class=(provider.container()) {
+            .embed-facade {
+                @if let Some(thumbnail) = thumbnail {
+                    img .embed-facade-thumbnail src=(thumbnail) alt="" loading="lazy";
+                }
+                button .embed-facade-play type="button" aria-label={ "読み込む - Load: " (title) } { "▶" }
+                a .embed-facade-title href=(link) { (title) }
+                p .embed-facade-notice {
+                    "クリックすると" (hosts) "から読み込みます。 - Clicking loads content from " (hosts) "."
+                }
+                template { (markup) }
+            }
+        }
+    })
+}
+
+/// Where pictures of `link` can be downloaded, best first. Empty when nothing can embed `link`
+/// or its platform has no thumbnails.
+pub fn thumbnails(data: &SiteData, link: &str) -> Result<Vec<String>, Error> {
+    let Some(provider) = provider(link) else {
+        return Ok(vec![]);
+    };
+    provider.thumbnails(data, link).map_err(|why| {
+        why.context(format!(
+            "{}のサムネイルが見つかりませんでした: {link} - Failed to find a thumbnail for {link} from {}",
+            provider.name(),
+            provider.name()
+        ))
+    })
+}
+
+/// The platform `link` is on, or `None` if it is not a URL.
+fn link_type(link: &str) -> Option<SocialLinkType> {
+    link.parse().ok()
+}
+
+/// Seconds into a video from a `t=` or `from=` query value: `90`, `90s` or `1h2m3s`.
+fn start_time(value: &str) -> Result<u32, Error> {
+    let invalid = || Error::msg(format!("invalid start time `{value}`"));
+    if let Ok(seconds) = value.parse() {
+        return Ok(seconds);
+    }
+
+    let mut seconds = 0;
+    let mut number = String::new();
+    for char in value.chars() {
+        if char.is_ascii_digit() {
+            number.push(char);
+            continue;
+        }
+        let unit = match char {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        let amount: u32 = number.parse().map_err(|_| invalid())?;
+        seconds = amount
+            .checked_mul(unit)
+            .and_then(|amount| amount.checked_add(seconds))
+            .ok_or_else(invalid)?;
+        number.clear();
+    }
+    if !number.is_empty() || value.is_empty() {
+        return Err(invalid());
+    }
+    Ok(seconds)
+}
+
+/// Asks the oEmbed `endpoint` of a platform about `link`, through the embed cache. `None` when
+/// building offline and it was never asked before.
+fn oembed(data: &SiteData, endpoint: &str, link: &str) -> Result<Option<OEmbed>, Error> {
+    let url = format!("{endpoint}?format=json&url={}", encode(link));
+    data.embed_cache.get(&url, || {
+        let response = reqwest::blocking::get(&url)?;
+
+        if response.status() != StatusCode::OK {
+            return Err(Error::msg(format!(
+                "failed to get oembed from {endpoint}: {}",
+                response.status()
+            )));
+        }
+
+        Ok(response.json::<OEmbed>()?)
+    })
+}
+
+/// Stands in for an embed whose data could not be had, e.g. when building offline.
+fn link_card(link: &str) -> Markup {
+    html! {
+        a .embed-link-card href=(link) { (link) }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OEmbed {
+    /// `"1.0"` from most platforms, but `1` from SoundCloud.
+    pub version: serde_json::Value,
+    #[serde(rename = "type")]
+    pub r#type: String,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub title: Option<String>,
+    pub url: Option<String>,
+    pub author_name: Option<String>,
+    pub author_url: Option<String>,
+    pub provider_name: Option<String>,
+    pub provider_url: Option<String>,
+    pub html: Option<String>,
+    pub thumbnail_url: Option<String>,
+}
+
+pub fn jinja_embed(data: &SiteData, link: &str) -> Result<String, JinjaError> {
+    // Templates have no way to the image pipeline, so their facades go without thumbnails.
+    Ok(embed(data, link, None, None)
+        .map_err(|why| JinjaError::new(ErrorKind::InvalidOperation, format!("{why:#}")))?
+        .render()
+        .into_string())
+}
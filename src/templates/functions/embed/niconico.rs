@@ -0,0 +1,237 @@
+use super::{EmbedProvider, link_type, start_time};
+use crate::SiteData;
+use crate::die_linky::SocialLinkType;
+use anyhow::Error;
+use log::warn;
+use maud::{Markup, html};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Niconico videos, in their player.
+pub struct Niconico;
+
+impl EmbedProvider for Niconico {
+    fn name(&self) -> &'static str {
+        "ニコニコ動画 (Niconico)"
+    }
+
+    fn handles(&self, link: &str) -> bool {
+        link_type(link) == Some(SocialLinkType::NicoDouga)
+    }
+
+    fn render(&self, _data: &SiteData, link: &str) -> Result<Markup, Error> {
+        let nnd_video_link = video(link)?.embed_link();
+        Ok(html! {
+            iframe src=(nnd_video_link) title="Nicovideo Video Player" height="360" width="640" frameborder="0" allow="accelerometer; autoplay; clipboard-write; encrypted-media; gyroscope; picture-in-picture; web-share" referrerpolicy="strict-origin-when-cross-origin" allowfullscreen {}
+        })
+    }
+
+    fn thumbnails(&self, data: &SiteData, link: &str) -> Result<Vec<String>, Error> {
+        let Some(info) = thumb_info(data, &video(link)?.id)? else {
+            return Ok(vec![]);
+        };
+        // `.L` is the large version, which older videos do not have.
+        Ok(vec![
+            format!("{}.L", info.thumbnail_url),
+            info.thumbnail_url,
+        ])
+    }
+
+    fn origins(&self) -> &'static [&'static str] {
+        &["https://embed.nicovideo.jp"]
+    }
+
+    fn container(&self) -> &'static str {
+        "youtube-embed-container"
+    }
+
+    fn title(&self, data: &SiteData, link: &str) -> Option<String> {
+        let id = video(link).ok()?.id;
+        match thumb_info(data, &id) {
+            Ok(info) => Some(info?.title),
+            Err(why) => {
+                warn!("Failed to get the title of {link}: {why:#}");
+                None
+            }
+        }
+    }
+}
+
+/// What `getthumbinfo` tells about a video.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct ThumbInfo {
+    title: String,
+    thumbnail_url: String,
+}
+
+/// Asks niconico's `getthumbinfo` API about the video `id`, through the embed cache. `None` when
+/// building offline and it was never asked before.
+fn thumb_info(data: &SiteData, id: &str) -> Result<Option<ThumbInfo>, Error> {
+    let url = format!("https://ext.nicovideo.jp/api/getthumbinfo/{id}");
+    data.embed_cache.get(&url, || {
+        let response = reqwest::blocking::get(&url)?.error_for_status()?;
+        parse_thumb_info(&response.text()?)
+    })
+}
+
+fn parse_thumb_info(xml: &str) -> Result<ThumbInfo, Error> {
+    if !xml.contains(r#"status="ok""#) {
+        let code = tag(xml, "code").unwrap_or_default();
+        return Err(Error::msg(format!("getthumbinfo answered an error {code}")));
+    }
+    Ok(ThumbInfo {
+        title: tag(xml, "title").ok_or(Error::msg("no title in getthumbinfo"))?,
+        thumbnail_url: tag(xml, "thumbnail_url")
+            .ok_or(Error::msg("no thumbnail_url in getthumbinfo"))?,
+    })
+}
+
+/// The text of the first `<name>` element. `getthumbinfo` is flat enough not to need more.
+fn tag(xml: &str, name: &str) -> Option<String> {
+    let start = xml.find(&format!("<{name}>"))? + name.len() + 2;
+    let end = start + xml[start..].find(&format!("</{name}>"))?;
+    Some(
+        xml[start..end]
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&#39;", "'")
+            .replace("&amp;", "&"),
+    )
+}
+
+#[derive(Debug)]
+struct Video {
+    id: String,
+    /// Seconds in, from `from=` or `t=`.
+    start: Option<u32>,
+}
+
+impl Video {
+    fn embed_link(&self) -> String {
+        match self.start {
+            Some(start) => format!("https://embed.nicovideo.jp/watch/{}?from={start}", self.id),
+            None => format!("https://embed.nicovideo.jp/watch/{}", self.id),
+        }
+    }
+}
+
+/// Reads the video out of `nicovideo.jp/watch/ID` and `nico.ms/ID` links, for `sm`, `nm` and
+/// `so` IDs.
+fn video(link: &str) -> Result<Video, Error> {
+    let url = Url::parse(link)?;
+    let id = url
+        .path_segments()
+        .ok_or(Error::msg("invalid nnd link"))?
+        .find(|segment| is_video_id(segment))
+        .ok_or(Error::msg("no id in nnd link"))?
+        .to_string();
+    let query = |key: &str| {
+        url.query_pairs()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.into_owned())
+    };
+    let start = query("from")
+        .or_else(|| query("t"))
+        .map(|value| start_time(&value))
+        .transpose()?;
+    Ok(Video { id, start })
+}
+
+fn is_video_id(segment: &str) -> bool {
+    ["sm", "nm", "so"].iter().any(|prefix| {
+        segment.strip_prefix(prefix).is_some_and(|number| {
+            !number.is_empty() && number.chars().all(|char| char.is_ascii_digit())
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video_of(link: &str) -> (String, Option<u32>) {
+        let video = video(link).unwrap();
+        (video.id, video.start)
+    }
+
+    #[test]
+    fn watch_links() {
+        for link in [
+            "https://www.nicovideo.jp/watch/sm42575907",
+            "https://nicovideo.jp/watch/sm42575907?ref=share",
+            "https://sp.nicovideo.jp/watch/sm42575907",
+        ] {
+            assert_eq!(video_of(link), ("sm42575907".to_string(), None), "{link}");
+        }
+    }
+
+    #[test]
+    fn short_links() {
+        assert_eq!(
+            video_of("https://nico.ms/sm42575907"),
+            ("sm42575907".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn nm_and_so_ids() {
+        assert_eq!(
+            video_of("https://www.nicovideo.jp/watch/nm2829323"),
+            ("nm2829323".to_string(), None)
+        );
+        assert_eq!(
+            video_of("https://nico.ms/so38016254"),
+            ("so38016254".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn start_times() {
+        assert_eq!(
+            video_of("https://www.nicovideo.jp/watch/sm42575907?from=75"),
+            ("sm42575907".to_string(), Some(75))
+        );
+        assert_eq!(
+            video("https://nico.ms/sm42575907?from=12")
+                .unwrap()
+                .embed_link(),
+            "https://embed.nicovideo.jp/watch/sm42575907?from=12"
+        );
+    }
+
+    #[test]
+    fn getthumbinfo_answers() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<nicovideo_thumb_response status="ok">
+  <thumb>
+    <video_id>sm42575907</video_id>
+    <title>ボカロ &amp; 曲</title>
+    <thumbnail_url>https://nicovideo.cdn.nimg.jp/thumbnails/42575907/42575907.1234</thumbnail_url>
+  </thumb>
+</nicovideo_thumb_response>"#;
+        assert_eq!(
+            parse_thumb_info(xml).unwrap(),
+            ThumbInfo {
+                title: "ボカロ & 曲".to_string(),
+                thumbnail_url: "https://nicovideo.cdn.nimg.jp/thumbnails/42575907/42575907.1234"
+                    .to_string(),
+            }
+        );
+
+        let deleted = r#"<nicovideo_thumb_response status="fail"><error><code>DELETED</code></error></nicovideo_thumb_response>"#;
+        assert!(parse_thumb_info(deleted).is_err());
+    }
+
+    #[test]
+    fn links_without_a_video() {
+        for link in [
+            "https://www.nicovideo.jp/user/127501006",
+            "https://www.nicovideo.jp/watch/smile",
+            "https://nico.ms/",
+            "https://www.nicovideo.jp/watch/sm42575907?from=later",
+        ] {
+            assert!(video(link).is_err(), "{link}");
+        }
+    }
+}
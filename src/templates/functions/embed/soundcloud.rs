@@ -0,0 +1,51 @@
+use super::{EmbedProvider, link_type, oembed};
+use crate::SiteData;
+use crate::die_linky::SocialLinkType;
+use anyhow::Error;
+use maud::{Markup, html};
+use url::Url;
+use urlencoding::encode;
+
+/// SoundCloud tracks and sets, in their widget player.
+pub struct Soundcloud;
+
+impl EmbedProvider for Soundcloud {
+    fn name(&self) -> &'static str {
+        "SoundCloud"
+    }
+
+    fn handles(&self, link: &str) -> bool {
+        link_type(link) == Some(SocialLinkType::Soundcloud)
+    }
+
+    fn render(&self, _data: &SiteData, link: &str) -> Result<Markup, Error> {
+        let url = Url::parse(link)?;
+        if url
+            .path_segments()
+            .is_none_or(|mut segments| segments.all(str::is_empty))
+        {
+            return Err(Error::msg("no track or set in soundcloud link"));
+        }
+        let is_set = url.path().contains("/sets/");
+        let player_link = format!(
+            "https://w.soundcloud.com/player/?url={}&auto_play=false&visual={is_set}",
+            encode(link)
+        );
+        let height = if is_set { "450" } else { "166" };
+
+        Ok(html! {
+            iframe src=(player_link) title="SoundCloud Player" height=(height) width="100%" frameborder="0" allow="autoplay" loading="lazy" {}
+        })
+    }
+
+    fn thumbnails(&self, data: &SiteData, link: &str) -> Result<Vec<String>, Error> {
+        Ok(oembed(data, "https://soundcloud.com/oembed", link)?
+            .and_then(|oembed| oembed.thumbnail_url)
+            .into_iter()
+            .collect())
+    }
+
+    fn origins(&self) -> &'static [&'static str] {
+        &["https://w.soundcloud.com"]
+    }
+}
@@ -0,0 +1,63 @@
+use super::{EmbedProvider, link_type, oembed};
+use crate::SiteData;
+use crate::die_linky::SocialLinkType;
+use anyhow::Error;
+use maud::{Markup, html};
+use url::Url;
+
+/// Spotify tracks, albums, playlists and artists, in their player.
+pub struct Spotify;
+
+impl EmbedProvider for Spotify {
+    fn name(&self) -> &'static str {
+        "Spotify"
+    }
+
+    fn handles(&self, link: &str) -> bool {
+        link_type(link) == Some(SocialLinkType::Spotify)
+    }
+
+    fn render(&self, _data: &SiteData, link: &str) -> Result<Markup, Error> {
+        let (kind, id) = item(link)?;
+        let embed_link = format!("https://open.spotify.com/embed/{kind}/{id}");
+        // A single track fits in the compact player; everything else lists its tracks.
+        let height = if kind == "track" { "152" } else { "352" };
+
+        Ok(html! {
+            iframe src=(embed_link) title="Spotify Player" height=(height) width="100%" frameborder="0" allow="autoplay; clipboard-write; encrypted-media; fullscreen; picture-in-picture" loading="lazy" style="border-radius: 12px;" {}
+        })
+    }
+
+    fn thumbnails(&self, data: &SiteData, link: &str) -> Result<Vec<String>, Error> {
+        item(link)?;
+        Ok(oembed(data, "https://open.spotify.com/oembed", link)?
+            .and_then(|oembed| oembed.thumbnail_url)
+            .into_iter()
+            .collect())
+    }
+
+    fn origins(&self) -> &'static [&'static str] {
+        &["https://open.spotify.com"]
+    }
+}
+
+/// The kind of thing `link` is and its ID, e.g. `("track", "4uLU6hMCjMI75M1A2tKUQC")`. Skips
+/// the language prefix of links like `/intl-ja/track/…`.
+fn item(link: &str) -> Result<(String, String), Error> {
+    let url = Url::parse(link)?;
+    let mut segments = url
+        .path_segments()
+        .ok_or(Error::msg("invalid spotify link"))?
+        .skip_while(|segment| segment.starts_with("intl-"));
+    let kind = segments.next().unwrap_or_default();
+    if !["track", "album", "playlist", "artist"].contains(&kind) {
+        return Err(Error::msg(
+            "spotify link is not to a track, album, playlist or artist",
+        ));
+    }
+    let id = segments
+        .next()
+        .filter(|id| !id.is_empty())
+        .ok_or(Error::msg("no id in spotify link"))?;
+    Ok((kind.to_string(), id.to_string()))
+}
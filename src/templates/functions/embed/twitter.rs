@@ -0,0 +1,38 @@
+use super::{EmbedProvider, link_type};
+use crate::SiteData;
+use crate::die_linky::SocialLinkType;
+use anyhow::Error;
+use maud::{Markup, html};
+
+/// Posts on X, formerly Twitter, through their widget script.
+pub struct Twitter;
+
+impl EmbedProvider for Twitter {
+    fn name(&self) -> &'static str {
+        "X (Twitter)"
+    }
+
+    fn handles(&self, link: &str) -> bool {
+        matches!(
+            link_type(link),
+            Some(SocialLinkType::Twitter | SocialLinkType::Xitter)
+        )
+    }
+
+    fn render(&self, _data: &SiteData, link: &str) -> Result<Markup, Error> {
+        Ok(html! {
+            blockquote .twitter-tweet {
+                script async src="https://platform.twitter.com/widgets.js" charset="utf-8";
+                a href=(link);
+            }
+        })
+    }
+
+    fn thumbnails(&self, _data: &SiteData, _link: &str) -> Result<Vec<String>, Error> {
+        Ok(vec![])
+    }
+
+    fn origins(&self) -> &'static [&'static str] {
+        &["https://platform.twitter.com"]
+    }
+}
@@ -0,0 +1,198 @@
+use super::{EmbedProvider, link_type, oembed, start_time};
+use crate::SiteData;
+use crate::die_linky::SocialLinkType;
+use anyhow::Error;
+use log::warn;
+use maud::{Markup, html};
+use url::Url;
+
+/// YouTube videos, in their player.
+pub struct Youtube;
+
+impl EmbedProvider for Youtube {
+    fn name(&self) -> &'static str {
+        "YouTube"
+    }
+
+    fn handles(&self, link: &str) -> bool {
+        link_type(link) == Some(SocialLinkType::Youtube)
+    }
+
+    fn render(&self, data: &SiteData, link: &str) -> Result<Markup, Error> {
+        // Behind a facade, the visitor has already clicked play once.
+        let embed_link = video(link)?.embed_link(data.config.embed_facades);
+
+        Ok(html! {
+            iframe src=(embed_link) title="Youtube Video Player" height="360" width="640" frameborder="0" allow="accelerometer; autoplay; clipboard-write; encrypted-media; gyroscope; picture-in-picture; web-share" referrerpolicy="strict-origin-when-cross-origin" allowfullscreen style="width: 100%;"{}
+        })
+    }
+
+    fn thumbnails(&self, _data: &SiteData, link: &str) -> Result<Vec<String>, Error> {
+        let id = video(link)?.id;
+        // Older videos have no `maxresdefault`, but every video has `hqdefault`.
+        Ok(vec![
+            format!("https://img.youtube.com/vi/{id}/maxresdefault.jpg"),
+            format!("https://img.youtube.com/vi/{id}/hqdefault.jpg"),
+        ])
+    }
+
+    fn origins(&self) -> &'static [&'static str] {
+        &["https://www.youtube-nocookie.com"]
+    }
+
+    fn container(&self) -> &'static str {
+        "youtube-embed-container"
+    }
+
+    fn title(&self, data: &SiteData, link: &str) -> Option<String> {
+        match oembed(data, "https://www.youtube.com/oembed", link) {
+            Ok(oembed) => oembed?.title,
+            Err(why) => {
+                warn!("Failed to get the title of {link}: {why:#}");
+                None
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Video {
+    id: String,
+    /// Seconds in, from `t=` or `start=`.
+    start: Option<u32>,
+}
+
+impl Video {
+    /// The player on `youtube-nocookie.com`, which leaves no cookies until the video is played.
+    fn embed_link(&self, autoplay: bool) -> String {
+        let mut link = format!("https://www.youtube-nocookie.com/embed/{}", self.id);
+        let mut params = vec![];
+        if let Some(start) = self.start {
+            params.push(format!("start={start}"));
+        }
+        if autoplay {
+            params.push("autoplay=1".to_string());
+        }
+        if !params.is_empty() {
+            link = format!("{link}?{}", params.join("&"));
+        }
+        link
+    }
+}
+
+/// Reads the video out of any of the shapes YouTube links come in: `watch?v=ID` on
+/// `www.`, `m.` and `music.youtube.com`, `youtu.be/ID`, and `/shorts/ID`, `/live/ID` and
+/// `/embed/ID`.
+fn video(link: &str) -> Result<Video, Error> {
+    let url = Url::parse(link)?;
+    let segments = url.path_segments().map(Iterator::collect).unwrap_or(vec![]);
+    let query = |key: &str| {
+        url.query_pairs()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.into_owned())
+    };
+
+    let id = match (url.domain(), segments.as_slice()) {
+        (Some("youtu.be"), [id, ..]) => Some(id.to_string()),
+        (_, ["watch", ..]) => query("v"),
+        (_, ["shorts" | "live" | "embed", id, ..]) => Some(id.to_string()),
+        _ => None,
+    }
+    .ok_or(Error::msg("no video in youtube link"))?;
+    if id.len() != 11
+        || !id
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '-' || char == '_')
+    {
+        return Err(Error::msg(format!("invalid youtube video id `{id}`")));
+    }
+
+    let start = query("t")
+        .or_else(|| query("start"))
+        .map(|value| start_time(&value))
+        .transpose()?;
+    Ok(Video { id, start })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video_of(link: &str) -> (String, Option<u32>) {
+        let video = video(link).unwrap();
+        (video.id, video.start)
+    }
+
+    #[test]
+    fn watch_links() {
+        for link in [
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+            "https://youtube.com/watch?v=dQw4w9WgXcQ",
+            "https://m.youtube.com/watch?v=dQw4w9WgXcQ",
+            "https://music.youtube.com/watch?v=dQw4w9WgXcQ&si=abc",
+            "https://www.youtube.com/watch?list=PL123&v=dQw4w9WgXcQ",
+        ] {
+            assert_eq!(video_of(link), ("dQw4w9WgXcQ".to_string(), None), "{link}");
+        }
+    }
+
+    #[test]
+    fn short_links() {
+        assert_eq!(
+            video_of("https://youtu.be/dQw4w9WgXcQ?si=abc"),
+            ("dQw4w9WgXcQ".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn shorts_live_and_embed_links() {
+        for link in [
+            "https://www.youtube.com/shorts/dQw4w9WgXcQ",
+            "https://youtube.com/live/dQw4w9WgXcQ?feature=share",
+            "https://www.youtube.com/embed/dQw4w9WgXcQ",
+        ] {
+            assert_eq!(video_of(link), ("dQw4w9WgXcQ".to_string(), None), "{link}");
+        }
+    }
+
+    #[test]
+    fn start_times() {
+        assert_eq!(
+            video_of("https://youtu.be/dQw4w9WgXcQ?t=42"),
+            ("dQw4w9WgXcQ".to_string(), Some(42))
+        );
+        assert_eq!(
+            video_of("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=1m30s"),
+            ("dQw4w9WgXcQ".to_string(), Some(90))
+        );
+        assert_eq!(
+            video_of("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=1h0m5s"),
+            ("dQw4w9WgXcQ".to_string(), Some(3605))
+        );
+        assert_eq!(
+            video("https://www.youtube.com/embed/dQw4w9WgXcQ?start=7")
+                .unwrap()
+                .embed_link(false),
+            "https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ?start=7"
+        );
+        assert_eq!(
+            video("https://youtu.be/dQw4w9WgXcQ?t=7")
+                .unwrap()
+                .embed_link(true),
+            "https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ?start=7&autoplay=1"
+        );
+    }
+
+    #[test]
+    fn links_without_a_video() {
+        for link in [
+            "https://www.youtube.com/@seeyoumayday",
+            "https://www.youtube.com/watch?list=PL123",
+            "https://youtu.be/",
+            "https://www.youtube.com/watch?v=short",
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=soon",
+        ] {
+            assert!(video(link).is_err(), "{link}");
+        }
+    }
+}
@@ -1,13 +1,14 @@
+use crate::routes::{Route, Routes};
 use maud::{Markup, html};
 
-pub fn member(to_link: &str) -> Markup {
+pub fn member(routes: &Routes, to_link: &str) -> Markup {
     html! {
-        a href=(format!("/members/{to_link}"))  {
+        a href=(routes.url(Route::Member(to_link)))  {
             (to_link)
         }
     }
 }
 
-pub fn jinja_member(to_link: &str) -> String {
-    member(to_link).into_string()
+pub fn jinja_member(routes: &Routes, to_link: &str) -> String {
+    member(routes, to_link).into_string()
 }
@@ -8,7 +8,11 @@ use maud::{Markup, html};
 // use minijinja::ErrorKind;
 
 pub fn sns_icon(context: &Context<SiteData>, link: &str) -> Result<Markup, RuntimeError> {
-    let temp = link.parse::<SocialLinkType>().unwrap();
+    let temp = link.parse::<SocialLinkType>().map_err(|why| {
+        RuntimeError::msg(format!(
+            "{link}はリンクではありません: {why} - {link} is not a link: {why}"
+        ))
+    })?;
     let sns_url_icon = temp.to_svg_icon();
     let special_style = match temp {
         // horrible, horrible hack but we roll with it ig
@@ -1,5 +1,7 @@
 use crate::SiteData;
 use crate::metadata::Metadata;
+use crate::publish::ContentStatus;
+use crate::routes::Route;
 use crate::templates::base::base;
 use crate::templates::partials::navbar::Sections;
 use crate::util::image;
@@ -8,11 +10,12 @@ use hauchiwa::RuntimeError;
 use maud::{Markup, html};
 
 pub fn index(context: &Context<SiteData>) -> Result<Markup, RuntimeError> {
+    let routes = &context.get_globals().data.routes;
     let meta = Metadata {
         page_title: "東京大学ボカロP同好会 - University of Tokyo Vocaloid Producer Club"
             .to_string(),
         page_image: Some("images/circle-photo.jpg".to_string()),
-        canonical_link: "/index.html".to_string(),
+        canonical_link: routes.url(Route::Home),
         section: Sections::Home,
         description: Some(
             "東京大学ボカロP同好会は、ボーカロイド楽曲の制作を通じて交流するサークルです。"
@@ -20,6 +23,7 @@ pub fn index(context: &Context<SiteData>) -> Result<Markup, RuntimeError> {
         ),
         author: None,
         date: None,
+        status: ContentStatus::Published,
     };
 
     let content = html! {
@@ -27,7 +31,7 @@ pub fn index(context: &Context<SiteData>) -> Result<Markup, RuntimeError> {
             .container {
                 h2 { "ボカロ、作ろう。" }
                 p { "ボーカロイド楽曲の制作を通じて交流するサークルです。" }
-                a href="/join.html" .btn { "入会案内" }
+                a href=(routes.url(Route::Join)) .btn { "入会案内" }
             }
         }
 
@@ -85,7 +89,7 @@ pub fn index(context: &Context<SiteData>) -> Result<Markup, RuntimeError> {
                         }
                         div style="margin-top: auto;" {
                             .click-button{
-                                a href="/works.html" {
+                                a href=(routes.url(Route::Works)) {
                                 p {
                                     "全曲一覧になる"
                                 }
@@ -1,6 +1,8 @@
 use crate::{
     SiteData,
     metadata::Metadata,
+    publish::ContentStatus,
+    routes::Route,
     templates::{base::base, partials::navbar::Sections},
 };
 use hauchiwa::{Context, RuntimeError};
@@ -10,11 +12,12 @@ pub fn join_vocadou(sack: &Context<SiteData>) -> Result<Markup, RuntimeError> {
     let meta = Metadata {
         page_title: "入会希望者へ - Joining Vocaloid Producer Club".to_string(),
         page_image: Some("images/circle-photo.jpg".to_string()),
-        canonical_link: "/join.html".to_string(),
+        canonical_link: sack.get_globals().data.routes.url(Route::Join),
         section: Sections::Join,
         author: None,
         date: None,
         description: None,
+        status: ContentStatus::Published,
     };
 
     let content = html! {
@@ -5,12 +5,14 @@ use crate::album::AlbumMeta;
 use crate::member::MemberMeta;
 use crate::metadata::Metadata;
 use crate::news::NewsMeta;
+use crate::publish::ContentStatus;
+use crate::routes::Route;
 use crate::sitemap::SiteMap;
 use crate::templates::base::base;
 use crate::templates::functions::sns::sns_icon;
-use crate::templates::news::{post_reference, post_thumbnail};
+use crate::templates::news::post_thumbnail;
 use crate::templates::partials::navbar::Sections;
-use crate::templates::works::{album_reference, thumbnail_link, work_reference};
+use crate::templates::works::{thumbnail_link, work_reference};
 use crate::util::image;
 use crate::work::WorkMeta;
 use hauchiwa::Context;
@@ -40,11 +42,12 @@ pub fn members(sack: &Context<SiteData>, site_map: &SiteMap) -> Result<Markup, R
     let metadata = Metadata {
         page_title: "メンバー紹介 - 東京大学ボカロP同好会".to_string(),
         page_image: None,
-        canonical_link: "/members.html".to_string(),
+        canonical_link: sack.get_globals().data.routes.url(Route::Members),
         section: Sections::Members,
         description: Some("東京大学ボカロP同好会のメンバー紹介".to_string()),
         author: None,
         date: None,
+        status: ContentStatus::Published,
     };
 
     base(sack, &metadata, None, inner)
@@ -52,9 +55,10 @@ pub fn members(sack: &Context<SiteData>, site_map: &SiteMap) -> Result<Markup, R
 
 pub fn member_card(sack: &Context<SiteData>, member: &MemberMeta) -> Result<Markup, RuntimeError> {
     let member_links_len = member.links.len();
+    let routes = &sack.get_globals().data.routes;
     Ok(html! {
         .member-item {
-            a .member-link href=(format!("/members/{}.html", member.ascii_name)) {
+            a .member-link href=(routes.url(Route::Member(&member.ascii_name))) {
                 .member-card {
                     .member-image .img-placeholder {
                         img .member-image .img-placeholder src=(image(sack, format!("images/icon/{}.jpg", member.ascii_name))?) alt=(member.name);
@@ -187,7 +191,7 @@ pub fn member_detail(
                 }
 
                 .back-button  {
-                    a href="../members.html" class="margin-top: 50px;" {
+                    a href=(sack.get_globals().data.routes.url(Route::Members)) class="margin-top: 50px;" {
                         "メンバー一覧に戻る"
                     }
                 }
@@ -195,7 +199,7 @@ pub fn member_detail(
         }
     };
 
-    let metadata = MemberMeta::to_metadata(member.clone());
+    let metadata = MemberMeta::to_metadata(member.clone(), &sack.get_globals().data.routes);
 
     base(sack, &metadata, None, inner)
 }
@@ -218,7 +222,7 @@ pub fn featured_work_item_detail(
             }
 
             .click-button{
-                a href=(format!("/works/releases/{}.html", work_ref)) {
+                a href=(sack.get_globals().data.routes.url(Route::Work(item))) {
                     p { "詳しく見る" }
                 }
             }
@@ -237,7 +241,7 @@ pub fn featured_post_detail(
             }
             .post-info {
                 h3 .post-card-title style="text-align: start; margin-bottom: 0px;" {
-                    a href=(format!("/news/{}.html", post_reference(item))) {
+                    a href=(sack.get_globals().data.routes.url(Route::Post(item))) {
                         (item.title)
                     }
                 }
@@ -269,7 +273,7 @@ pub fn featured_album_detail(
             }
             .post-info {
                 h3 .post-card-title style="text-align: start; margin-bottom: 0px;" {
-                    a href=(format!("/works/albums/{}.html", album_reference(&album_meta.title, &album_meta.front_cover))) {
+                    a href=(sack.get_globals().data.routes.url(Route::Album(album_meta))) {
                         (album_meta.title)
                     }
                 }
@@ -6,4 +6,5 @@ pub mod join;
 pub mod members;
 pub mod news;
 pub mod partials;
+pub mod redirect;
 pub mod works;
@@ -1,10 +1,11 @@
 use crate::news::NewsMeta;
+use crate::routes::Route;
 use crate::sitemap::SiteMap;
 use crate::templates::base::base;
 use crate::templates::functions::sns::sns_icon;
 use crate::templates::partials::navbar::Sections;
-use crate::util::{image, shorten};
-use crate::{SiteData, metadata::Metadata};
+use crate::util::{image, short_hash, shorten, slugify};
+use crate::{SiteData, metadata::Metadata, publish::ContentStatus};
 use base64::Engine;
 use base64::prelude::BASE64_URL_SAFE_NO_PAD;
 use hauchiwa::Context;
@@ -46,11 +47,12 @@ pub fn news_posts(
     let metadata = Metadata {
         page_title: "ニュース".to_string(),
         page_image: None,
-        canonical_link: "/news.html".to_string(),
+        canonical_link: sack.get_globals().data.routes.url(Route::News),
         section: Sections::News,
         description: Some("東京大学ボカロP同好会のニュース".to_string()),
         author: None,
         date: None,
+        status: ContentStatus::Published,
     };
 
     base(sack, &metadata, Some(&[]), inner)
@@ -62,6 +64,7 @@ pub fn post_card(
     name_map: &HashMap<String, String>,
 ) -> Result<Markup, RuntimeError> {
     let author_name = post_meta.author.as_ref().map(|author| name_map.get(author).ok_or(RuntimeError::msg("Could not find author. Does the member page exist? Did you remember to type in the ascii name? Did you mistype it? Yell at peg for more info".to_string())));
+    let routes = &context.get_globals().data.routes;
 
     Ok(html! {
         .post-card {
@@ -70,7 +73,7 @@ pub fn post_card(
             }
             .post-info {
                 h3 .post-card-title {
-                    a href=(format!("/news/{}.html", post_reference(post_meta))) {
+                    a href=(routes.url(Route::Post(post_meta))) {
                         (post_meta.title)
                     }
                 }
@@ -78,7 +81,7 @@ pub fn post_card(
                     (post_meta.date)
                 }
                 @if let Some(author) = author_name && let Some(ascii_author) = &post_meta.author {
-                    a href=(format!("/members/{}.html", ascii_author)) { p { (author?) } }
+                    a href=(routes.url(Route::Member(ascii_author))) { p { (author?) } }
                 } @else {
                     p { "東大ボカロP同好会" }
                 }
@@ -102,6 +105,7 @@ pub fn post_detail(
     name_map: &HashMap<String, String>,
 ) -> Result<Markup, RuntimeError> {
     let author_name = post_meta.author.as_ref().map(|author| name_map.get(author).ok_or(RuntimeError::msg("Could not find author. Does the member page exist? Did you remember to type in the ascii name? Did you mistype it? Yell at peg for more info".to_string())));
+    let routes = &sack.get_globals().data.routes;
 
     let inner = html! {
         section #post-detail {
@@ -114,7 +118,7 @@ pub fn post_detail(
                         h2 { (post_meta.title) }
                         p { (post_meta.date) }
                         @if let Some(author) = author_name && let Some(ascii_author) = &post_meta.author {
-                            a href=(format!("/members/{}.html", ascii_author)) { p { (author?) } }
+                            a href=(routes.url(Route::Member(ascii_author))) { p { (author?) } }
                         } @else {
                             p { "東大ボカロP同好会" }
                         }
@@ -136,7 +140,7 @@ pub fn post_detail(
             }
 
             .back-button{
-                a href="../news.html" {
+                a href=(routes.url(Route::News)) {
                     "ニュース目録一覧に戻る"
                 }
             }
@@ -146,11 +150,12 @@ pub fn post_detail(
     let metadata = Metadata {
         page_title: post_meta.title.clone(),
         page_image: Some(post_thumbnail(sack, post_meta)?),
-        canonical_link: format!("/news/{}.html", post_reference(post_meta)),
+        canonical_link: routes.url(Route::Post(post_meta)),
         section: Sections::NewsPost,
         description: Some(shorten(content)),
         author: post_meta.author.clone(),
         date: Some(post_meta.date.to_string()),
+        status: post_meta.status,
     };
 
     base(sack, &metadata, Some(&[]), inner)
@@ -165,6 +170,16 @@ pub fn post_thumbnail(sack: &Context<SiteData>, item: &NewsMeta) -> Result<Strin
     // TODO: Get thumbnail from SNS post.
 }
 
+/// The page name of a post: its `slug`, or else its date and romanized title. A title that
+/// cannot be romanized is replaced by its [`short_hash`].
+pub fn post_slug(meta: &NewsMeta) -> String {
+    if let Some(slug) = meta.slug.as_deref().and_then(slugify) {
+        return slug;
+    }
+    let title = slugify(&meta.title).unwrap_or_else(|| short_hash(&meta.title));
+    format!("{}-{title}", meta.date)
+}
+
 pub fn post_reference(meta: &NewsMeta) -> String {
     let authorhash = seahash::hash(
         meta.author
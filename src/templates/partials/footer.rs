@@ -3,6 +3,7 @@ use hauchiwa::{Context, RuntimeError};
 use maud::{Markup, html};
 
 pub fn footer(context: &Context<SiteData>) -> Result<Markup, RuntimeError> {
+    let config = &context.get_globals().data.config;
     Ok(html! {
         footer {
             .container {
@@ -10,7 +11,9 @@ pub fn footer(context: &Context<SiteData>) -> Result<Markup, RuntimeError> {
                     "© 2025 東京大学ボカロP同好会"
                 }
                 .social-links .social-footer {
-                    (sns_icon(context, "https://x.com/toudaivocadou")?)
+                    @for link in &config.footer_links {
+                        (sns_icon(context, link)?)
+                    }
                 }
             }
         }
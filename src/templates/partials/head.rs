@@ -1,5 +1,6 @@
 use crate::SiteData;
-use crate::metadata::{Metadata, render_metadata};
+use crate::metadata::{Metadata, canonical_url, render_metadata};
+use crate::serve::serve_scripts;
 use camino::Utf8PathBuf;
 use hauchiwa::loader::{Script, Style};
 use hauchiwa::{Context, ContextError, RuntimeError};
@@ -14,8 +15,17 @@ pub fn html_head(
     let style_path = Utf8PathBuf::from("styles/style.css");
     let style = sack.get::<Style>(&style_path)?.path.as_str();
 
+    // Embeds anywhere on the page, even those put there later by other scripts, wait for it.
+    let facades = sack
+        .get_globals()
+        .data
+        .config
+        .embed_facades
+        .then_some("embed.js");
     let scripts = scripts
         .iter()
+        .copied()
+        .chain(facades)
         .map(|script| {
             let res = sack
                 .get::<Script>(format!("js/{}", script))
@@ -35,6 +45,7 @@ pub fn html_head(
             meta charset="UTF-8";
             meta name="viewport" content="width=device-width, initial-scale=1.0";
             (render_metadata(sack, metadata)?)
+            link rel="canonical" href=(canonical_url(sack, metadata));
             link rel="stylesheet" href=(style);
             link rel="icon" type="image/x-icon" href="/favicon.ico";
             @for script_url in scripts {
@@ -42,6 +53,7 @@ pub fn html_head(
                     script src=(s) {}
                 }
             }
+            (serve_scripts(sack))
         }
     })
 }
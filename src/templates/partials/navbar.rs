@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+use crate::routes::{Route, Routes};
 use maud::{Markup, html};
 use serde::{Deserialize, Serialize};
 
@@ -23,23 +24,23 @@ impl Display for Sections {
     }
 }
 
-pub fn navbar(current_section: Sections) -> Markup {
+pub fn navbar(routes: &Routes, current_section: Sections) -> Markup {
     html! {
         header {
             div .container {
-                a href="/index.html" {
+                a href=(routes.url(Route::Home)) {
                     h1 {
                         "東京大学ボカロP同好会"
                     }
                 }
                 nav {
                     ul {
-                        (navbar_item("/index.html", current_section == Sections::Home, "ホーム"))
-                        (navbar_item("/members.html", current_section == Sections::Members || current_section == Sections::MemberProfile, "メンバー紹介"))
-                        (navbar_item("/index.html#activities", current_section == Sections::Activities, "活動内容"))
-                        (navbar_item("/join.html", current_section == Sections::Join, "入会案内"))
-                        (navbar_item("/works.html", current_section == Sections::Works || current_section == Sections::WorksPost, "リリース"))
-                        (navbar_item("/news.html", current_section == Sections::News || current_section == Sections::NewsPost, "ニュース"))
+                        (navbar_item(&routes.url(Route::Home), current_section == Sections::Home, "ホーム"))
+                        (navbar_item(&routes.url(Route::Members), current_section == Sections::Members || current_section == Sections::MemberProfile, "メンバー紹介"))
+                        (navbar_item(&format!("{}#activities", routes.url(Route::Home)), current_section == Sections::Activities, "活動内容"))
+                        (navbar_item(&routes.url(Route::Join), current_section == Sections::Join, "入会案内"))
+                        (navbar_item(&routes.url(Route::Works), current_section == Sections::Works || current_section == Sections::WorksPost, "リリース"))
+                        (navbar_item(&routes.url(Route::News), current_section == Sections::News || current_section == Sections::NewsPost, "ニュース"))
 
                     }
                 }
@@ -0,0 +1,23 @@
+use maud::{DOCTYPE, Markup, html};
+
+/// Stands in at a page's old URL and sends visitors on to `to`.
+pub fn redirect(to: &str) -> Markup {
+    html! {
+        (DOCTYPE)
+        html lang="ja" {
+            head {
+                meta charset="utf-8";
+                title { "移動しました - Moved" }
+                link rel="canonical" href=(to);
+                meta name="robots" content="noindex";
+                meta http-equiv="refresh" content=(format!("0; url={to}"));
+            }
+            body {
+                p {
+                    "このページは移動しました - This page has moved: "
+                    a href=(to) { (to) }
+                }
+            }
+        }
+    }
+}
@@ -1,21 +1,23 @@
 use crate::SiteData;
-use crate::album::AlbumMeta;
-use crate::die_linky::SocialLinkType;
+use crate::album::{AlbumMeta, TracklistTrack};
 use crate::metadata::Metadata;
+use crate::publish::ContentStatus;
+use crate::read::ContentFile;
+use crate::routes::{Route, Routes};
 use crate::sitemap::SiteMap;
 use crate::templates::base::base;
-use crate::templates::functions::embed::embed;
+use crate::templates::functions::embed::{audio_player, embed};
 use crate::templates::functions::sns::sns_icon;
 use crate::templates::partials::navbar::Sections;
-use crate::util::{image, shorten};
+use crate::thumbnails::thumbnail_path;
+use crate::util::{SvgData, audio, image, short_hash, shorten, slugify};
 use crate::work::WorkMeta;
 use base64::Engine;
 use base64::prelude::BASE64_URL_SAFE_NO_PAD;
-use hauchiwa::{Context, RuntimeError};
+use hauchiwa::loader::Image;
+use hauchiwa::{Context, ContextError, RuntimeError};
 use maud::{Markup, PreEscaped, html};
 use std::collections::HashMap;
-use std::str::FromStr;
-use url::Url;
 
 pub fn works(
     sack: &Context<SiteData>,
@@ -23,6 +25,7 @@ pub fn works(
     name_map: &HashMap<String, String>,
 ) -> Result<Markup, RuntimeError> {
     // TODO: pagination. this will get ungodly long. yell at peng if we get >100!
+    let routes = &sack.get_globals().data.routes;
 
     let inner = html! {
         section #hero {
@@ -95,11 +98,12 @@ pub fn works(
     let metadata = Metadata {
         page_title: "リリース".to_string(),
         page_image: None,
-        canonical_link: "/works.html".to_string(),
+        canonical_link: routes.url(Route::Works),
         section: Sections::Works,
         description: Some("東京大学ボカロP同好会のメンバーの作品展示館".to_string()),
         author: None,
         date: None,
+        status: ContentStatus::Published,
     };
 
     base(sack, &metadata, Some(&[]), inner)
@@ -111,13 +115,14 @@ pub fn work_card(
     name_map: &HashMap<String, String>,
 ) -> Result<Markup, RuntimeError> {
     let author_name = name_map.get(&work_meta.author).ok_or(RuntimeError::msg("Could not find author. Does the member page exist? Did you remember to type in the ascii name? Did you mistype it?".to_string()))?;
+    let routes = &sack.get_globals().data.routes;
 
     Ok(html! {
         .work-item {
-            a .member-link href=(format!("/works/releases/{}.html", work_reference(&work_meta.title, &work_meta.author))) {
+            a .member-link href=(routes.url(Route::Work(work_meta))) {
                 .work-card {
                     h4 .member-info {
-                        a .member-link href=(format!("/works/releases/{}.html", work_reference(&work_meta.title, &work_meta.author))){
+                        a .member-link href=(routes.url(Route::Work(work_meta))) {
                             (work_meta.title)
                         }
                     }
@@ -125,7 +130,7 @@ pub fn work_card(
                         img .work-item-thumb src=(thumbnail_link(sack, work_meta)?) alt=(work_meta.title) {}
                     }
                     .work-description {
-                        a href=(format!("/members/{}.html", work_meta.author)) {
+                        a href=(routes.url(Route::Member(&work_meta.author))) {
                             p .member-role {
                                 (author_name)
                             }
@@ -157,6 +162,28 @@ pub fn album_reference(title: &str, cover: &str) -> String {
     BASE64_URL_SAFE_NO_PAD.encode(combined.to_le_bytes())
 }
 
+/// The page name of a work: its `slug`, or else its author and romanized title. A title that
+/// cannot be romanized is replaced by its [`short_hash`].
+pub fn work_slug(work_meta: &WorkMeta) -> String {
+    if let Some(slug) = work_meta.slug.as_deref().and_then(slugify) {
+        return slug;
+    }
+    let author = slugify(&work_meta.author).unwrap_or_else(|| short_hash(&work_meta.author));
+    let title = slugify(&work_meta.title).unwrap_or_else(|| short_hash(&work_meta.title));
+    format!("{author}-{title}")
+}
+
+/// The page name of an album: its `slug`, or else its romanized title, or else the
+/// [`short_hash`] of its title.
+pub fn album_slug(album_meta: &AlbumMeta) -> String {
+    album_meta
+        .slug
+        .as_deref()
+        .and_then(slugify)
+        .or_else(|| slugify(&album_meta.title))
+        .unwrap_or_else(|| short_hash(&album_meta.title))
+}
+
 pub fn album_card(
     sack: &Context<SiteData>,
     album_meta: &AlbumMeta,
@@ -170,16 +197,13 @@ pub fn album_card(
             .take(18)
             .collect::<String>()
     );
+    let routes = &sack.get_globals().data.routes;
     Ok(html! {
         .work-item {
-            a .member-link href=(
-                format!("/works/albums/{}.html", album_reference(&album_meta.title, &album_meta.front_cover))
-            ) {
+            a .member-link href=(routes.url(Route::Album(album_meta))) {
                 .work-card {
                     h4 .member-info {
-                        a href=(
-                            format!("/works/albums/{}.html", album_reference(&album_meta.title, &album_meta.front_cover))
-                        ) {
+                        a href=(routes.url(Route::Album(album_meta))) {
                             (album_meta.title)
                         }
                     }
@@ -206,17 +230,57 @@ pub fn album_card(
     })
 }
 
+/// The page of the work a track is, if it is `on_site`. Looked up among the built `works`, as
+/// unlisted ones are not in the site map.
+fn track_link(
+    routes: &Routes,
+    works: &[ContentFile<WorkMeta>],
+    track: &TracklistTrack,
+) -> Option<String> {
+    if !track.on_site {
+        return None;
+    }
+    works
+        .iter()
+        .map(|work| &work.data.meta)
+        .find(|work| work.title == track.title && work.author == track.author)
+        .map(|work| routes.url(Route::Work(work)))
+}
+
+/// How long a piece of audio runs, as `m:ss`, or `h:mm:ss` from an hour up.
+fn runtime(duration_seconds: i32) -> String {
+    let (hours, minutes, seconds) = (
+        duration_seconds / 3600,
+        duration_seconds / 60 % 60,
+        duration_seconds % 60,
+    );
+    match hours {
+        0 => format!("{minutes}:{seconds:02}"),
+        _ => format!("{hours}:{minutes:02}:{seconds:02}"),
+    }
+}
+
+/// The length of the whole album, when the length of every track is known.
+fn total_runtime(tracklist: &[TracklistTrack]) -> Option<i32> {
+    if tracklist.is_empty() {
+        return None;
+    }
+    tracklist.iter().map(|track| track.duration_seconds).sum()
+}
+
 pub fn album_detail(
     sack: &Context<SiteData>,
     album_meta: &AlbumMeta,
     name_map: &HashMap<String, String>,
     content: &str,
+    works: &[ContentFile<WorkMeta>],
 ) -> Result<Markup, RuntimeError> {
+    let routes = &sack.get_globals().data.routes;
     let contributors = album_meta.contributors.iter().map(|contributor| {
-        let ascii_name = name_map.get(contributor).unwrap();
+        let name = name_map.get(contributor).unwrap();
         html! {
-            a href=(format!("/members/{}.html", ascii_name)) {
-                (contributor)
+            a href=(routes.url(Route::Member(contributor))) {
+                (name)
             }
         }
     });
@@ -261,14 +325,17 @@ pub fn album_detail(
             .member-works-container {
                 section #tracklist {
                     h2 { "トラックリスト" }
+                    @if let Some(total) = total_runtime(&album_meta.tracklist) {
+                        p .tracklist-total { "合計 " (runtime(total)) }
+                    }
                     dl .tracklist-list  {
                         @for (number, track) in album_meta.tracklist.iter().enumerate() {
                             .tracklist-track {
                                 dt .track-title {
                                     h2 {
                                         (number + 1) ". "
-                                        @if track.on_site {
-                                            a href=(work_reference(&track.title, &track.author)) {
+                                        @if let Some(link) = track_link(routes, works, track) {
+                                            a href=(link) {
                                                 (track.title)
                                             }
                                         } @else if let Some(link) = &track.link {
@@ -285,19 +352,13 @@ pub fn album_detail(
                                     @if track.external_author {
                                         (track.author)
                                     } @else {
-                                        a href=(format!("/members/{}.html", &track.author)) {
+                                        a href=(routes.url(Route::Member(&track.author))) {
                                             (name_map.get(&track.author).ok_or(RuntimeError::msg("User does not exist in album"))?)
                                         }
                                     }
                                 }
                                 @if let Some(duration_seconds) = track.duration_seconds {
-                                    dd .track-length {
-                                        ({
-                                            let minutes = duration_seconds / 60;
-                                            let seconds = duration_seconds % 60;
-                                            format!("{}:{:02}", minutes, seconds)
-                                        })
-                                    }
+                                    dd .track-length { (runtime(duration_seconds)) }
                                 }
                             }
                         }
@@ -322,9 +383,12 @@ pub fn album_detail(
                     h2 { "試聴動画" }
                     @if let Some(crossfade_demonstration) = &album_meta.crossfade_demonstration {
                         .work-youtube-container {
-                            .youtube-embed-container {
-                                (embed(crossfade_demonstration.as_str())?)
-                            }
+                            (embed(
+                                &sack.get_globals().data,
+                                crossfade_demonstration.as_str(),
+                                link_thumbnail(sack, crossfade_demonstration.as_str())?.as_deref(),
+                                Some(&album_meta.title),
+                            )?)
                         }
                     } @else {
                         p .work-no-description {
@@ -346,7 +410,7 @@ pub fn album_detail(
                                 p {"イラスト: " (album_meta.front_cover_illustrator) }
                             }
                             @else {
-                                a href=(format!("/members/{}.html", album_meta.front_cover_illustrator)) {
+                                a href=(routes.url(Route::Member(&album_meta.front_cover_illustrator))) {
                                     p { "イラスト: " (name_map.get(&album_meta.front_cover_illustrator).ok_or(RuntimeError::msg("did not find front cover illustrator on site"))?) }
                                 }
                             }
@@ -360,7 +424,7 @@ pub fn album_detail(
                                 @if imglnk.illustrator_is_not_on_site {
                                     p { "イラスト: " (imglnk.illustrator) }
                                 } @else {
-                                    a href=(format!("/members/{}.html", imglnk.illustrator)) {
+                                    a href=(routes.url(Route::Member(&imglnk.illustrator))) {
                                         p { "イラスト: " (name_map.get(&imglnk.illustrator).ok_or(RuntimeError::msg("did not find illustrator on site"))?) }
                                     }
                                 }
@@ -371,7 +435,7 @@ pub fn album_detail(
 
 
                 .back-button{
-                    a href="../../works.html" {
+                    a href=(routes.url(Route::Works)) {
                         "リリース集合一覧に戻る"
                     }
                 }
@@ -382,14 +446,12 @@ pub fn album_detail(
     let metadata = Metadata {
         page_title: album_meta.title.clone(),
         page_image: None,
-        canonical_link: format!(
-            "/works/albums/{}.html",
-            album_reference(&album_meta.title, &album_meta.front_cover)
-        ),
+        canonical_link: routes.url(Route::Album(album_meta)),
         section: Sections::AlbumPost,
         description: Some(album_meta.short.clone()),
         author: Some(album_meta.contributors_str(name_map)),
         date: Some(album_meta.release_date.to_string()),
+        status: album_meta.status,
     };
     base(sack, &metadata, Some(&[]), inner)
 }
@@ -401,6 +463,7 @@ pub fn work_detail(
     content: &str,
 ) -> Result<Markup, RuntimeError> {
     let author_name = name_map.get(&work_meta.author).expect("Could not find author. Does the member page exist? Did you remember to type in the ascii name? Did you mistype it? Yell at peg for more info");
+    let routes = &sack.get_globals().data.routes;
 
     let inner = html! {
         section #work-section {
@@ -418,8 +481,11 @@ pub fn work_detail(
                         }
                         .work-date {
                             p { (work_meta.date) }
+                            @if let Some(duration_seconds) = work_meta.duration_seconds {
+                                p .work-length { "再生時間 " (runtime(duration_seconds)) }
+                            }
                         }
-                        a .member-role .member-bio href=(format!("/members/{}.html", work_meta.author)) { p { (author_name) } }
+                        a .member-role .member-bio href=(routes.url(Route::Member(&work_meta.author))) { p { (author_name) } }
                         @if let Some(short) = &work_meta.short {
                             p .work-bio { (short) }
                         }
@@ -435,16 +501,22 @@ pub fn work_detail(
             .member-works-container {
                 section .work-featured-work-container {
                     h2 { "作品リンク" }
+                    @if let Some(file) = &work_meta.file {
+                        (audio_player(&audio(sack, file)?, &work_meta.title))
+                    }
                     @if let Some(link) = &work_meta.link {
-                        .youtube-embed-container {
-                            (embed(link.as_str())?)
-                        }
+                        (embed(
+                            &sack.get_globals().data,
+                            link.as_str(),
+                            link_thumbnail(sack, link.as_str())?.as_deref(),
+                            Some(&work_meta.title),
+                        )?)
                         .click-button {
                             a href=(link) alt=(&work_meta.title) {
                                 p { "現本に行く" }
                             }
                         }
-                    } @else {
+                    } @else if work_meta.file.is_none() {
                         p .work-no-description {
                             em { "リンクがありません。" }
                         }
@@ -465,7 +537,7 @@ pub fn work_detail(
                 }
 
                 .back-button{
-                    a href="../../works.html" {
+                    a href=(routes.url(Route::Works)) {
                         "リリース集合一覧に戻る"
                     }
                 }
@@ -475,53 +547,48 @@ pub fn work_detail(
 
     let page_image = match &work_meta.display {
         crate::work::CoverOrImage::Cover(cover) => Some(cover.to_string()),
-        crate::work::CoverOrImage::Link(url) => Some(url.to_string()),
+        crate::work::CoverOrImage::Link(url) => link_thumbnail(sack, url.as_str())?,
         crate::work::CoverOrImage::AudioFile(_) => None,
     };
 
     let metadata = Metadata {
         page_title: work_meta.title.clone(),
         page_image,
-        canonical_link: format!("/works/releases/{}.html", work_meta.title),
+        canonical_link: routes.url(Route::Work(work_meta)),
         section: Sections::WorksPost,
         description: Some(work_meta.short.clone().unwrap_or(shorten(content))),
         author: Some(work_meta.author.clone()),
         date: Some(work_meta.date.to_string()),
+        status: work_meta.status,
     };
     base(sack, &metadata, Some(&[]), inner)
 }
 
+/// Stands in for the cover of works that are only an audio file.
+const AUDIO_COVER: &str = "assets/audio-cover.svg";
+
 pub fn thumbnail_link(sack: &Context<SiteData>, meta: &WorkMeta) -> Result<String, RuntimeError> {
     match &meta.display {
         crate::work::CoverOrImage::Cover(cover) => image(sack, cover),
-        crate::work::CoverOrImage::Link(url) => get_link_image_thumb(sack, url.as_str()),
-        crate::work::CoverOrImage::AudioFile(_audio_file) => Ok("".to_string()),
+        crate::work::CoverOrImage::Link(url) => match link_thumbnail(sack, url.as_str())? {
+            Some(thumbnail) => Ok(thumbnail),
+            None => image(sack, "images/gray.jpg"),
+        },
+        crate::work::CoverOrImage::AudioFile(_) => {
+            Ok(sack.get::<SvgData>(AUDIO_COVER)?.path.to_string())
+        }
     }
 }
 
-pub fn get_link_image_thumb(sack: &Context<SiteData>, link: &str) -> Result<String, RuntimeError> {
-    let url_type =
-        SocialLinkType::from_str(link).map_err(|why| RuntimeError::msg(why.to_string()))?;
-    let url_parse = Url::parse(link).map_err(|why| RuntimeError::msg(why.to_string()))?;
-
-    match url_type {
-        SocialLinkType::Youtube => {
-            let youtube_video_id = url_parse
-                .query_pairs()
-                .find(|(key, _)| key == "v")
-                .ok_or(RuntimeError::msg("Invalid youtube id"))?
-                .1;
-            Ok(format!(
-                "https://img.youtube.com/vi/{}/maxresdefault.jpg",
-                youtube_video_id
-            ))
-        }
-        SocialLinkType::NicoDouga => {
-            // FIXME: NND is fucking cringe and you need some sort of key to download their thumbs.
-            // use request and fetch the thumbnails and host them locally.
-            // Until then, lol.
-            image(sack, "images/gray.jpg")
-        }
-        _ => image(sack, "images/gray.jpg"),
+/// The self-hosted thumbnail of `link`, if [`ThumbnailFetcher::fetch`](crate::thumbnails::ThumbnailFetcher::fetch)
+/// has downloaded one.
+pub fn link_thumbnail(
+    sack: &Context<SiteData>,
+    link: &str,
+) -> Result<Option<String>, RuntimeError> {
+    match sack.get::<Image>(thumbnail_path(link)) {
+        Ok(image) => Ok(Some(image.path.to_string())),
+        Err(ContextError::NotFound(_)) => Ok(None),
+        Err(why) => Err(why.into()),
     }
 }
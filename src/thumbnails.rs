@@ -0,0 +1,287 @@
+use crate::SiteData;
+use crate::read::SiteContent;
+use crate::templates::functions::embed::thumbnails;
+use anyhow::{Context as _, Error};
+use base64::Engine;
+use base64::prelude::BASE64_URL_SAFE_NO_PAD;
+use camino::{Utf8Path, Utf8PathBuf};
+use image::imageops::FilterType;
+use log::{info, warn};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use url::Url;
+
+/// Where downloaded thumbnails are kept, in the data root. The image loader picks them up from
+/// there like any other picture; commit them so builds need no network.
+pub const THUMBNAIL_DIR: &str = "images/thumbnails";
+
+/// Thumbnails are shrunk to fit in a square this wide before they are kept.
+const MAX_SIZE: u32 = 640;
+
+/// The thumbnail of `link`, relative to the data root, whether or not it has been downloaded.
+pub fn thumbnail_path(link: &str) -> Utf8PathBuf {
+    let key = BASE64_URL_SAFE_NO_PAD.encode(seahash::hash(link.as_bytes()).to_le_bytes());
+    Utf8Path::new(THUMBNAIL_DIR).join(format!("{key}.jpg"))
+}
+
+/// Downloads the thumbnails of works. Shared by every build in a `serve` session, so works added
+/// while serving get theirs, and links that failed are not tried again on every rebuild.
+#[derive(Debug)]
+pub struct ThumbnailFetcher {
+    /// Never download, the works are shown without thumbnails.
+    offline: bool,
+    /// Links downloaded or given up on since starting.
+    tried: Mutex<HashSet<String>>,
+}
+
+impl ThumbnailFetcher {
+    pub fn new(offline: bool) -> ThumbnailFetcher {
+        ThumbnailFetcher {
+            offline,
+            tried: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Downloads the thumbnail of the `link` of every work, unless it is already in
+    /// [`THUMBNAIL_DIR`] or was tried before. Works whose thumbnail cannot be had are warned
+    /// about and keep the placeholder. The image loader only sees what is downloaded on the next
+    /// build.
+    pub fn fetch(&self, data: &SiteData, content: &SiteContent) {
+        let links = {
+            let mut tried = self
+                .tried
+                .lock()
+                .unwrap_or_else(|poison| poison.into_inner());
+            content
+                .works
+                .iter()
+                .filter_map(|work| work.data.meta.link.as_ref().map(Url::as_str))
+                .filter(|link| !data.data_root.join(thumbnail_path(link)).exists())
+                .filter(|link| tried.insert(link.to_string()))
+                .collect::<Vec<_>>()
+        };
+        if links.is_empty() {
+            return;
+        }
+        if self.offline {
+            info!(
+                "Building offline, {} works will be shown without thumbnails.",
+                links.len()
+            );
+            return;
+        }
+
+        let client = match client() {
+            Ok(client) => client,
+            Err(why) => return warn!("Failed to set up the HTTP client for thumbnails: {why:#}"),
+        };
+        for link in links {
+            let path = data.data_root.join(thumbnail_path(link));
+            let downloaded = thumbnails(data, link).and_then(|sources| match sources.is_empty() {
+                true => Ok(false),
+                false => download(&client, &sources, &path).map(|_| true),
+            });
+            match downloaded {
+                Ok(true) => info!("Downloaded the thumbnail of {link} to {path}."),
+                Ok(false) => {}
+                Err(why) => warn!(
+                    "サムネイルをダウンロードできませんでした: {link} - Failed to download the thumbnail of {link}: {why:#}"
+                ),
+            }
+        }
+    }
+}
+
+fn client() -> Result<reqwest::blocking::Client, Error> {
+    Ok(reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(20))
+        .user_agent(concat!("toudaivocadou/", env!("CARGO_PKG_VERSION")))
+        .build()?)
+}
+
+/// Saves the first of `sources` that can be downloaded to `path`, shrunk to [`MAX_SIZE`], as a
+/// JPEG.
+fn download(
+    client: &reqwest::blocking::Client,
+    sources: &[String],
+    path: &Utf8Path,
+) -> Result<(), Error> {
+    let mut failures = vec![];
+    for source in sources {
+        let bytes = match client
+            .get(source)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.bytes())
+        {
+            Ok(bytes) => bytes,
+            Err(why) => {
+                failures.push(format!("{source}: {why}"));
+                continue;
+            }
+        };
+        let picture = match image::load_from_memory(&bytes) {
+            Ok(picture) => picture,
+            Err(why) => {
+                failures.push(format!("{source} is not a picture: {why}"));
+                continue;
+            }
+        };
+        let picture = if picture.width() > MAX_SIZE || picture.height() > MAX_SIZE {
+            picture.resize(MAX_SIZE, MAX_SIZE, FilterType::Lanczos3)
+        } else {
+            picture
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("creating {parent}"))?;
+        }
+        return picture
+            .to_rgb8()
+            .save_with_format(path, image::ImageFormat::Jpeg)
+            .with_context(|| format!("writing {path}"));
+    }
+    Err(Error::msg(failures.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, RgbImage};
+    use std::io::{BufRead, BufReader, Cursor, Write};
+    use std::net::TcpListener;
+
+    /// Answers `/wide.png` with a 1280×720 picture, `/small.png` with a 100×50 one, `/text` with
+    /// something that is not a picture and everything else with 404.
+    fn mock_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let root = format!("http://{}", listener.local_addr().unwrap());
+        let png = |width, height| {
+            let mut bytes = Cursor::new(vec![]);
+            RgbImage::new(width, height)
+                .write_to(&mut bytes, ImageFormat::Png)
+                .unwrap();
+            bytes.into_inner()
+        };
+        let (wide, small) = (png(1280, 720), png(100, 50));
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(&stream);
+                reader.read_line(&mut request_line).unwrap();
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+
+                let path = request_line.split_whitespace().nth(1).unwrap();
+                let (status, body) = match path {
+                    "/wide.png" => ("200 OK", wide.as_slice()),
+                    "/small.png" => ("200 OK", small.as_slice()),
+                    "/text" => ("200 OK", b"not a picture".as_slice()),
+                    _ => ("404 Not Found", b"".as_slice()),
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .unwrap();
+                stream.write_all(body).unwrap();
+            }
+        });
+        root
+    }
+
+    fn output(name: &str) -> Utf8PathBuf {
+        Utf8PathBuf::try_from(std::env::temp_dir())
+            .unwrap()
+            .join(format!("thumbnails-{}", std::process::id()))
+            .join(name)
+    }
+
+    #[test]
+    fn falls_back_to_the_next_source() {
+        let root = mock_server();
+        let path = output("fallback.jpg");
+        download(
+            &client().unwrap(),
+            &[
+                format!("{root}/maxresdefault.jpg"),
+                format!("{root}/small.png"),
+            ],
+            &path,
+        )
+        .unwrap();
+
+        let saved = image::open(&path).unwrap();
+        assert_eq!((saved.width(), saved.height()), (100, 50));
+        assert_eq!(
+            ImageFormat::from_path(&path).unwrap(),
+            ImageFormat::Jpeg,
+            "saved where the image loader looks for JPEGs"
+        );
+    }
+
+    #[test]
+    fn shrinks_large_pictures() {
+        let root = mock_server();
+        let path = output("wide.jpg");
+        download(&client().unwrap(), &[format!("{root}/wide.png")], &path).unwrap();
+
+        let saved = image::open(&path).unwrap();
+        assert_eq!((saved.width(), saved.height()), (MAX_SIZE, 360));
+    }
+
+    #[test]
+    fn reports_every_failed_source() {
+        let root = mock_server();
+        let path = output("missing.jpg");
+        let why = download(
+            &client().unwrap(),
+            &[
+                format!("{root}/maxresdefault.jpg"),
+                format!("{root}/hqdefault.jpg"),
+            ],
+            &path,
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(why.contains("/maxresdefault.jpg") && why.contains("/hqdefault.jpg"));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn rejects_what_is_not_a_picture() {
+        let root = mock_server();
+        let path = output("text.jpg");
+        assert!(download(&client().unwrap(), &[format!("{root}/text")], &path).is_err());
+        assert!(!path.exists());
+
+        let path = output("after-text.jpg");
+        download(
+            &client().unwrap(),
+            &[format!("{root}/text"), format!("{root}/small.png")],
+            &path,
+        )
+        .unwrap();
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn one_file_per_link() {
+        let youtube = thumbnail_path("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+        assert!(youtube.starts_with(THUMBNAIL_DIR));
+        assert_eq!(
+            youtube,
+            thumbnail_path("https://www.youtube.com/watch?v=dQw4w9WgXcQ")
+        );
+        assert_ne!(
+            youtube,
+            thumbnail_path("https://www.nicovideo.jp/watch/sm42575907")
+        );
+    }
+}
@@ -1,12 +1,12 @@
 use std::collections::HashMap;
-use std::sync::OnceLock;
 
 use crate::SiteData;
+use crate::config::SiteConfig;
 use crate::sitemap::SiteMap;
 use crate::templates::partials::navbar::Sections;
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use hauchiwa::RuntimeError;
-use hauchiwa::loader::Content;
+use hauchiwa::loader::{Content, Runtime};
 use hauchiwa::{Context, Page, loader::Image};
 use log::{error, info};
 use lol_html::{Settings, element, rewrite_str};
@@ -90,15 +90,34 @@ pub fn image(sack: &Context<SiteData>, path: impl AsRef<str>) -> Result<String,
     Ok(image.path.to_string())
 }
 
-pub struct AudioFile {}
+/// Audio files a work can have as its `file`, by extension.
+pub const AUDIO_EXTENSIONS: [&str; 5] = ["mp3", "ogg", "opus", "flac", "wav"];
 
-#[allow(dead_code)]
-pub fn audio(sack: &Context<SiteData>, path: impl AsRef<str>) -> Result<String, RuntimeError> {
-    let path = path.as_ref();
+/// An audio file from `audio/`, stored under its hash.
+pub struct AudioFile {
+    pub path: Utf8PathBuf,
+}
 
-    let audio_path = Utf8PathBuf::from(path);
-    let audio = sack.glob_one_with_file::<AudioFile>(audio_path.as_str())?;
-    Ok(audio.file.file.to_string())
+pub fn load_audio(rt: Runtime, data: Vec<u8>, extension: &str) -> anyhow::Result<AudioFile> {
+    let path = rt.store(&data, extension)?;
+    Ok(AudioFile { path })
+}
+
+/// The stored path of `file` in `audio/`, as written in a work's `file`. Files on the external
+/// host, e.g. `miku:audio/song.ogg`, are left for rewriting.
+pub fn audio(sack: &Context<SiteData>, file: impl AsRef<str>) -> Result<String, RuntimeError> {
+    let file = file.as_ref();
+    if file.starts_with("miku:") {
+        return Ok(file.to_string());
+    }
+
+    let audio = sack.get::<AudioFile>(audio_path(file))?;
+    Ok(audio.path.to_string())
+}
+
+/// Where the audio file `file` from front matter is, relative to the data root.
+pub fn audio_path(file: &str) -> Utf8PathBuf {
+    Utf8Path::new("audio").join(file.trim_start_matches("audio/"))
 }
 
 pub fn markup_to_page(
@@ -109,74 +128,198 @@ pub fn markup_to_page(
     rewrite_page(ctx, Page::html(path.as_ref(), &markup.0))
 }
 
-static SITE_URL: OnceLock<String> = OnceLock::new();
-
-pub fn set_site_url(value: String) {
-    SITE_URL.set(value).expect("Failed to set SITE_URL!")
-}
-
-// fn lnk(url: impl AsRef<str>) -> String {
-//     let root = SITE_URL.get().expect("SITE_URL not set!");
-
-//     slash_guard(root, url.as_ref())
-// }
+pub fn slash_guard(root: &str, thing: &str) -> String {
+    let root = root.trim_end_matches('/');
+    let thing = thing.trim_start_matches('/');
 
-// pub fn site_url() -> String {
-//     SITE_URL.get().expect("SITE_URL not set!").to_string()
-// }
-
-static EXTERNAL_BINARY_URL: OnceLock<String> = OnceLock::new();
+    // "." means the site is served from the domain root, so keep links site-relative.
+    if root.is_empty() || root == "." {
+        return format!("/{thing}");
+    }
 
-pub fn set_external_bin_url(value: String) {
-    EXTERNAL_BINARY_URL
-        .set(value)
-        .expect("Failed to set EXTERNAL_BINARY_URL!")
+    format!("{root}/{thing}")
 }
 
-static SITE_ROOT: OnceLock<String> = OnceLock::new();
+/// Lowercase ascii words joined by `-`, for URLs and file names, with kana romanized the
+/// Hepburn way. `None` if nothing is left, or if `text` has kanji: they cannot be read without a
+/// dictionary, so callers fall back to [`short_hash`] and anything important should get a hand
+/// written `slug`.
+pub fn slugify(text: &str) -> Option<String> {
+    if text.chars().any(is_kanji) {
+        return None;
+    }
+    let slug = any_ascii::any_ascii(&romanize_kana(text))
+        .to_ascii_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-");
+    Some(slug).filter(|slug| !slug.is_empty())
+}
 
-pub fn set_site_root(site_root: String) {
-    SITE_ROOT.set(site_root).expect("SITE_ROOT not set!")
+/// A short name for text that [`slugify`] cannot romanize, the same as long as the text is.
+pub fn short_hash(text: &str) -> String {
+    format!("{:08x}", seahash::hash(text.as_bytes()) >> 32)
 }
 
-pub fn site_root() -> &'static str {
-    SITE_ROOT.get().expect("SITE_ROOT not set!")
+fn is_kanji(c: char) -> bool {
+    matches!(
+        c,
+        '々' | '〆' | '〇'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{20000}'..='\u{3134F}'
+    )
 }
 
-// fn lnk_s3(url: impl AsRef<str>) -> String {
-//     let url = url.as_ref();
-
-//     if let Ok(u) = Url::parse(url) {
-//         warn!("Warning: Passed link to lnk that is already a url");
-//         return u.to_string();
-//     }
-
-//     let root = if url.starts_with("miku:") {
-//         EXTERNAL_BINARY_URL
-//             .get()
-//             .expect("EXTERNAL_BINARY_URL not set!")
-//             .to_string()
-//     } else {
-//         site_url()
-//     };
-
-//     slash_guard(&root, url)
-// // }
-
-fn slash_guard(root: &str, thing: &str) -> String {
-    if root == "." {
-        return if thing.starts_with("/") {
-            format!("/{}", thing)
-        } else {
-            thing.to_string()
+/// `text` with hiragana and katakana romanized the Hepburn way, without marking long vowels.
+/// Everything else is left as it is.
+fn romanize_kana(text: &str) -> String {
+    // Katakana sit at a fixed distance from the matching hiragana.
+    let kana = text
+        .chars()
+        .map(|c| match c {
+            'ァ'..='ヶ' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+            '・' => ' ',
+            _ => c,
+        })
+        .collect::<Vec<char>>();
+
+    let mut syllables = Vec::<String>::new();
+    let mut doubled = false;
+    let mut index = 0;
+    while index < kana.len() {
+        let c = kana[index];
+        index += 1;
+        let Some(romaji) = kana_romaji(c) else {
+            match c {
+                'っ' => doubled = true,
+                // Long vowels are left unmarked, as in `tokyo`.
+                'ー' => {}
+                _ => syllables.push(c.to_string()),
+            }
+            continue;
         };
-    }
 
-    if thing.starts_with("/") {
-        format!("{root}{}", thing)
-    } else {
-        format!("{root}/{}", thing)
+        let mut syllable = romaji.to_string();
+        if let Some(&small) = kana.get(index)
+            && let Some(combined) = combine(romaji, small)
+        {
+            syllable = combined;
+            index += 1;
+        }
+        if doubled && let Some(first) = syllable.chars().next().filter(|c| !"aiueon".contains(*c)) {
+            syllable.insert(
+                0,
+                if syllable.starts_with("ch") {
+                    't'
+                } else {
+                    first
+                },
+            );
+        }
+        doubled = false;
+        syllables.push(syllable);
     }
+    syllables.concat()
+}
+
+/// A syllable followed by a small kana, e.g. `きゃ` or `ティ`, or `None` if they do not combine.
+fn combine(romaji: &str, small: char) -> Option<String> {
+    let vowel = match small {
+        'ゃ' | 'ゅ' | 'ょ' => {
+            let consonant = romaji.strip_suffix('i').filter(|stem| !stem.is_empty())?;
+            let vowel = &kana_romaji(small)?[1..];
+            return Some(match consonant {
+                "sh" | "ch" | "j" => format!("{consonant}{vowel}"),
+                _ => format!("{consonant}y{vowel}"),
+            });
+        }
+        'ぁ' | 'ぃ' | 'ぅ' | 'ぇ' | 'ぉ' => kana_romaji(small)?,
+        _ => return None,
+    };
+    let consonant = match romaji {
+        "u" => "w",
+        "i" => "y",
+        _ => romaji.strip_suffix(['a', 'i', 'u', 'e', 'o'])?,
+    };
+    (!consonant.is_empty()).then(|| format!("{consonant}{vowel}"))
+}
+
+fn kana_romaji(c: char) -> Option<&'static str> {
+    Some(match c {
+        'あ' | 'ぁ' => "a",
+        'い' | 'ぃ' | 'ゐ' => "i",
+        'う' | 'ぅ' => "u",
+        'え' | 'ぇ' | 'ゑ' => "e",
+        'お' | 'ぉ' | 'を' => "o",
+        'か' | 'ゕ' => "ka",
+        'き' => "ki",
+        'く' => "ku",
+        'け' | 'ゖ' => "ke",
+        'こ' => "ko",
+        'が' => "ga",
+        'ぎ' => "gi",
+        'ぐ' => "gu",
+        'げ' => "ge",
+        'ご' => "go",
+        'さ' => "sa",
+        'し' => "shi",
+        'す' => "su",
+        'せ' => "se",
+        'そ' => "so",
+        'ざ' => "za",
+        'じ' | 'ぢ' => "ji",
+        'ず' | 'づ' => "zu",
+        'ぜ' => "ze",
+        'ぞ' => "zo",
+        'た' => "ta",
+        'ち' => "chi",
+        'つ' => "tsu",
+        'て' => "te",
+        'と' => "to",
+        'だ' => "da",
+        'で' => "de",
+        'ど' => "do",
+        'な' => "na",
+        'に' => "ni",
+        'ぬ' => "nu",
+        'ね' => "ne",
+        'の' => "no",
+        'は' => "ha",
+        'ひ' => "hi",
+        'ふ' => "fu",
+        'へ' => "he",
+        'ほ' => "ho",
+        'ば' => "ba",
+        'び' => "bi",
+        'ぶ' => "bu",
+        'べ' => "be",
+        'ぼ' => "bo",
+        'ぱ' => "pa",
+        'ぴ' => "pi",
+        'ぷ' => "pu",
+        'ぺ' => "pe",
+        'ぽ' => "po",
+        'ま' => "ma",
+        'み' => "mi",
+        'む' => "mu",
+        'め' => "me",
+        'も' => "mo",
+        'や' | 'ゃ' => "ya",
+        'ゆ' | 'ゅ' => "yu",
+        'よ' | 'ょ' => "yo",
+        'ら' => "ra",
+        'り' => "ri",
+        'る' => "ru",
+        'れ' => "re",
+        'ろ' => "ro",
+        'わ' | 'ゎ' => "wa",
+        'ん' => "n",
+        'ゔ' => "vu",
+        _ => return None,
+    })
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -188,7 +331,7 @@ pub fn render_metadata_and_final_page<C, RenderFn>(
     data: &Content<C>,
     page_type: Sections,
     friendly_name: &str,
-    final_url: String,
+    final_url: Utf8PathBuf,
     render_fn: RenderFn,
 ) -> Result<Page, RuntimeError>
 where
@@ -233,7 +376,7 @@ where
     Ok(Page::html(final_url, work_rendered?.into_string()))
 }
 
-pub fn rewrite_settings(site_url: &str) -> Settings<'_, '_> {
+pub fn rewrite_settings(config: &SiteConfig) -> Settings<'_, '_> {
     Settings {
         element_content_handlers: vec![
             // element!("script", |element| {
@@ -259,7 +402,7 @@ pub fn rewrite_settings(site_url: &str) -> Settings<'_, '_> {
                     }
                 };
 
-                let rewritten_lnk = rewrite_link(site_url, referring_to)?;
+                let rewritten_lnk = rewrite_link(config, referring_to)?;
                 element.set_attribute("href", &rewritten_lnk)?;
 
                 Ok(())
@@ -273,7 +416,7 @@ pub fn rewrite_settings(site_url: &str) -> Settings<'_, '_> {
                 };
 
                 // check if this refer is relative
-                let rewritten_lnk = rewrite_link(site_url, referring_to)?;
+                let rewritten_lnk = rewrite_link(config, referring_to)?;
                 element.set_attribute("src", &rewritten_lnk)?;
 
                 Ok(())
@@ -289,15 +432,13 @@ pub fn rewrite_html(text: &str, settings: Settings<'_, '_>) -> Result<String, Ru
 
 pub fn rewrite_page(context: &Context<SiteData>, mut page: Page) -> Result<Page, RuntimeError> {
     let build_id = context.get_globals().data.build_id;
-    let site_url = &context.get_globals().data.site_url;
+    let config = &context.get_globals().data.config;
     let pgpath = &page.path;
 
     if page.path.starts_with("/") {
         panic!("page path {} starts with illegal /", page.path);
     }
-    if let Some(extension) = page.path.extension()
-        && extension != "html"
-    {
+    if page.path.extension() != Some("html") {
         info!(
             "BUILD-{}: Skipping page {}, not a html page.",
             build_id, pgpath
@@ -306,16 +447,24 @@ pub fn rewrite_page(context: &Context<SiteData>, mut page: Page) -> Result<Page,
     }
 
     info!("BUILD-{}: Rewriting {}", build_id, pgpath);
-    let out = rewrite_html(&page.text, rewrite_settings(site_url))?;
+    let out = rewrite_html(&page.text, rewrite_settings(config))?;
     page.text = out;
 
     Ok(page)
 }
 
-pub fn rewrite_link(site_url: &str, link: String) -> Result<String, anyhow::Error> {
+pub fn rewrite_link(config: &SiteConfig, link: String) -> Result<String, anyhow::Error> {
     if link.starts_with("..") || link.starts_with("#") || link.starts_with("https://") {
         return Ok(link);
     }
+    // binaries too large for the repo live on the external host, e.g. `miku:audio/song.ogg`
+    if let Some(striped) = link.strip_prefix("miku:") {
+        return Ok(slash_guard(&config.external_binary_url, striped));
+    }
+    // site-relative links, so the site also works under a path prefix
+    if link.starts_with("/") && !link.starts_with("//") {
+        return Ok(slash_guard(&config.site_url, &link));
+    }
     if let Ok(mut url) = Url::parse(&link) {
         url.set_scheme("https")
             .map_err(|_| anyhow::Error::msg("???"))?;
@@ -324,14 +473,48 @@ pub fn rewrite_link(site_url: &str, link: String) -> Result<String, anyhow::Erro
     // run our transformations
     if link.starts_with("./") {
         let striped = link.strip_prefix("./").unwrap();
-        let fixed = slash_guard(site_url, striped);
+        let fixed = slash_guard(&config.site_url, striped);
         return Ok(fixed);
     }
     if link.starts_with(".") {
         let striped = link.strip_prefix(".").unwrap();
-        let fixed = slash_guard(site_url, striped);
+        let fixed = slash_guard(&config.site_url, striped);
         return Ok(fixed);
     }
 
     Ok(link)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kana_are_romanized() {
+        assert_eq!(
+            slugify("アナザーキャピタル").as_deref(),
+            Some("anazakyapitaru")
+        );
+        assert_eq!(
+            slugify("ばれてたらどうしよう").as_deref(),
+            Some("baretetaradoushiyou")
+        );
+        assert_eq!(
+            slugify("ちょっとマッチ・ティー").as_deref(),
+            Some("chottomatchi-ti")
+        );
+        assert_eq!(
+            slugify("3y3 / ファンタジー feat. ヴィ").as_deref(),
+            Some("3y3-fantaji-feat-vi")
+        );
+    }
+
+    #[test]
+    fn kanji_are_not_guessed() {
+        assert_eq!(slugify("この感情には後で名前を付けます"), None);
+        assert_eq!(slugify("アナザーキャピタル feat. 初音ミク"), None);
+        assert_eq!(slugify("!?"), None);
+        assert_eq!(short_hash("初音ミク"), short_hash("初音ミク"));
+        assert_eq!(short_hash("初音ミク").len(), 8);
+    }
+}
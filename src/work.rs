@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use toml::value::Date;
 use url::Url;
 
+use crate::publish::{ContentStatus, PublishAt};
+
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum CoverOrImage {
     Cover(String),
@@ -34,6 +36,14 @@ pub struct WorkMeta {
 
     #[serde(default)]
     pub duration_seconds: Option<i32>,
+    #[serde(default)]
+    pub status: ContentStatus,
+    #[serde(default)]
+    pub publish_at: Option<PublishAt>,
+    #[serde(default)]
+    pub slug: Option<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
 }
 
 #[derive(Clone, Debug, PartialOrd, PartialEq, Serialize, Deserialize)]
@@ -60,6 +70,14 @@ pub struct RawWorkMeta {
 
     #[serde(default)]
     pub duration_seconds: Option<i32>,
+    #[serde(default)]
+    pub status: ContentStatus,
+    #[serde(default)]
+    pub publish_at: Option<PublishAt>,
+    #[serde(default)]
+    pub slug: Option<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
 }
 
 #[derive(Clone, Debug, PartialOrd, PartialEq, Serialize, Deserialize)]